@@ -1,7 +1,59 @@
 mod parser;
 mod unit;
 
+#[cfg(feature = "tar")]
+pub use parser::tar::{load_units_from_tar, load_units_from_tar_with_options};
+#[cfg(feature = "tokio")]
+pub use parser::tokio::{
+    load_unit_async, load_unit_async_with_options, load_units_async, load_units_async_with_options,
+};
 pub use parser::{
-    generator::generate_unit_list as generate, loader::load_unit, loader::load_units,
+    cache::{CacheUpdate, UnitCache},
+    condition::evaluate_conditions,
+    export::{to_dot, to_dot_with, to_mermaid, to_mermaid_with},
+    generator::{
+        GenerateOptions, MissingDepPolicy, MissingDependencies, check_conflicts, critical_path,
+        critical_path_with, explain_order, explain_order_with, find_cycles, generate_for_target,
+        generate_levels, generate_levels_with, generate_order, generate_shutdown_order,
+        generate_unit_list as generate, generate_with, generate_with_missing, leaves, roots,
+        transitive_dependencies, transitive_dependencies_with, unreachable_from,
+    },
+    graph::UnitGraph,
+    loader::{
+        LoadOptions, LoadReport, UnitFormat, detect_type, load_enabled_units,
+        load_enabled_units_with_options, load_unit, load_unit_with_extensions,
+        load_unit_with_options, load_unit_with_registry, load_unit_with_validators, load_units,
+        load_units_filtered, load_units_glob, load_units_glob_with_options, load_units_iter,
+        load_units_iter_with_options, load_units_recursive, load_units_recursive_with_options,
+        load_units_report, load_units_strict, load_units_strict_with_options,
+        load_units_with_options, load_units_with_overrides, load_units_with_overrides_and_options,
+        load_units_with_registry, parse_unit, parse_unit_file, read_unit_file,
+    },
+    observer::{CollectingObserver, LoadWarning, LogObserver, Observer, ObserverHandle},
+    plan::{PLAN_SCHEMA_VERSION, generate_plan_json},
+    rules::{
+        DependencyKind, DependencyRule, check_dependency_types, check_once_targets,
+        filter_once_targets, lint_units,
+    },
+    stats::UnitStats,
+};
+pub use unit::builder::UnitFileBuilder;
+pub use unit::diff::{UnitChange, UnitDiff, diff_units};
+pub use unit::duration::{format_duration, parse_duration};
+pub use unit::errors::{
+    ErrorCategory, GraphBuildError, LocatedIssue, Location, UnitLoadError, ValidationIssue,
+};
+pub use unit::expand::UndefinedVars;
+pub use unit::merge::{
+    MergeStrategy, PartialConditionSection, PartialDependencySection, PartialInstallSection,
+    PartialServiceSection, PartialSocketSection, PartialTargetSection, PartialTimerSection,
+    PartialTodoSection, PartialUnitFile, PartialUnitSection,
+};
+pub use unit::registry::UnitTypeRegistry;
+pub use unit::set::UnitSet;
+pub use unit::types::{
+    ConditionSection, DependencySection, ExecCommand, InstallSection, RestartPolicy,
+    ServiceSection, SocketSection, TargetSection, TimerSection, TodoSection, UNIT_NAME_PUNCTUATION,
+    UnitFile, UnitSection, UnitType, WILDCARD_CHARS,
 };
-pub use unit::types::UnitType;
+pub use unit::validator::Validator;
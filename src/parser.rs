@@ -1,2 +1,18 @@
+pub mod cache;
+pub mod condition;
+pub mod env_file;
+pub mod export;
 pub mod generator;
+pub mod graph;
+pub mod include;
+pub mod ini;
 pub mod loader;
+pub mod observer;
+pub mod plan;
+pub mod rules;
+pub(crate) mod span;
+pub mod stats;
+#[cfg(feature = "tar")]
+pub mod tar;
+#[cfg(feature = "tokio")]
+pub mod tokio;
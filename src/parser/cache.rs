@@ -0,0 +1,116 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::parser::loader::{DirScanner, LoadOptions, sorted_entries};
+use crate::unit::errors::UnitLoadError;
+use crate::unit::types::UnitFile;
+
+/// Remembers the units loaded from a directory so that `reload` only re-parses files whose
+/// modification time changed.
+#[derive(Debug, Default)]
+pub struct UnitCache {
+    entries: HashMap<PathBuf, CachedUnit>,
+}
+
+#[derive(Debug)]
+struct CachedUnit {
+    modified: SystemTime,
+    unit: UnitFile,
+}
+
+/// Result of `UnitCache::reload`. `added`, `removed` and `changed` hold unit names relative
+/// to the previous reload; `changed` lists units whose file was re-parsed.
+#[derive(Debug, Default)]
+pub struct CacheUpdate {
+    pub units: Vec<UnitFile>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl UnitCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads `dir` the way `load_units` does, reusing cached units for files whose
+    /// modification time is unchanged. Files that disappeared or no longer load are dropped.
+    pub fn reload(&mut self, dir: impl AsRef<Path>) -> Result<CacheUpdate, UnitLoadError> {
+        self.reload_with_options(dir, &LoadOptions::default())
+    }
+
+    /// Like `reload`, but loads and reports warnings under `options`. Cached units are reused
+    /// as they are, so pass the same options on every reload.
+    pub fn reload_with_options(
+        &mut self,
+        dir: impl AsRef<Path>,
+        options: &LoadOptions,
+    ) -> Result<CacheUpdate, UnitLoadError> {
+        let previous: HashSet<String> = self
+            .entries
+            .values()
+            .filter(|cached| !cached.unit.is_template())
+            .map(|cached| cached.unit.unit.unit_name.clone())
+            .collect();
+
+        let mut scanner = DirScanner::new(None, options);
+        let mut entries = HashMap::new();
+        let mut reparsed = HashSet::new();
+
+        for (_, path) in sorted_entries(dir.as_ref())? {
+            if path.is_dir() || !scanner.wants(&path) {
+                continue;
+            }
+            let modified = match fs::metadata(&path).and_then(|m| m.modified()) {
+                Ok(modified) => modified,
+                Err(e) => {
+                    scanner.skip(path.clone(), UnitLoadError::ReadError(path, e));
+                    continue;
+                }
+            };
+
+            let unit = match self.entries.remove(&path) {
+                Some(cached) if cached.modified == modified => cached.unit,
+                _ => match scanner.read(&path) {
+                    Ok(unit) => {
+                        reparsed.insert(unit.unit.unit_name.clone());
+                        unit
+                    }
+                    Err(err) => {
+                        scanner.skip(path, err);
+                        continue;
+                    }
+                },
+            };
+
+            if scanner.accept(path.clone(), Ok(unit.clone())) {
+                entries.insert(path, CachedUnit { modified, unit });
+            }
+        }
+
+        self.entries = entries;
+
+        let units = scanner.report.units;
+        let current: HashSet<&str> = units.iter().map(|u| u.unit.unit_name.as_str()).collect();
+
+        let mut update = CacheUpdate::default();
+        for unit in &units {
+            let name = &unit.unit.unit_name;
+            if !previous.contains(name) {
+                update.added.push(name.clone());
+            } else if reparsed.contains(name) {
+                update.changed.push(name.clone());
+            }
+        }
+        update.removed =
+            previous.into_iter().filter(|name| !current.contains(name.as_str())).collect();
+        update.removed.sort();
+        update.units = units;
+
+        Ok(update)
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+
+use crate::unit::errors::GraphBuildError;
+use crate::unit::types::UnitFile;
+
+/// Drops every unit whose `[condition]` section does not hold, along with sockets whose
+/// service is dropped, and removes references to the dropped units from the remaining units'
+/// `before`, `after`, `wants`, `conflicts`, `wanted_by` and `required_by` lists. Returns the
+/// names of the dropped units. Fails without changing `units` if a remaining unit requires a
+/// dropped one.
+pub fn evaluate_conditions(units: &mut Vec<UnitFile>) -> Result<Vec<String>, GraphBuildError> {
+    let mut failed: Vec<bool> = units.iter().map(|unit| !unit.condition.holds()).collect();
+    if !failed.contains(&true) {
+        return Ok(Vec::new());
+    }
+
+    let mut dropped: HashSet<&String> = HashSet::new();
+    loop {
+        dropped.extend(units.iter().zip(&failed).filter(|&(_, &failed)| failed).flat_map(
+            |(unit, _)| std::iter::once(&unit.unit.unit_name).chain(&unit.unit.provides),
+        ));
+        let orphaned: Vec<usize> = (0..units.len())
+            .filter(|&i| !failed[i])
+            .filter(|&i| units[i].socket.as_ref().is_some_and(|s| dropped.contains(&s.service)))
+            .collect();
+        if orphaned.is_empty() {
+            break;
+        }
+        for i in orphaned {
+            failed[i] = true;
+        }
+    }
+
+    for (unit, _) in units.iter().zip(&failed).filter(|&(_, &failed)| !failed) {
+        if let Some(dep) = unit.dependency.requires.iter().find(|dep| dropped.contains(dep)) {
+            return Err(GraphBuildError::ConditionFailed(unit.unit.unit_name.clone(), dep.clone()));
+        }
+    }
+
+    let dropped: HashSet<String> = dropped.into_iter().cloned().collect();
+    let mut names = Vec::new();
+    let mut failed = failed.into_iter();
+    units.retain(|unit| {
+        let keep = !failed.next().unwrap_or(false);
+        if !keep {
+            names.push(unit.unit.unit_name.clone());
+        }
+        keep
+    });
+
+    for unit in units.iter_mut() {
+        let dependency = &mut unit.dependency;
+        for list in [
+            &mut dependency.needs_before,
+            &mut dependency.needs_after,
+            &mut dependency.wants,
+            &mut dependency.conflicts,
+        ] {
+            list.retain(|dep| !dropped.contains(dep));
+        }
+        let install = &mut unit.install;
+        for list in [&mut install.wanted_by, &mut install.required_by] {
+            list.retain(|target| !dropped.contains(target));
+        }
+    }
+
+    Ok(names)
+}
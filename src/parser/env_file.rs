@@ -0,0 +1,63 @@
+use std::{collections::HashMap, io, path::Path};
+
+use crate::parser::loader::Files;
+use crate::unit::errors::UnitLoadError;
+use crate::unit::types::TodoSection;
+
+const OPTIONAL_PREFIX: char = '-';
+
+/// Reads every file in `todo.env_file`, resolved relative to the directory of `source`, and
+/// adds its variables to `todo.env` without overriding inline entries. Later files win over
+/// earlier ones. Blank lines and `#` comments are skipped, and values may be wrapped in single
+/// or double quotes.
+pub(crate) fn apply_env_files(
+    todo: &mut TodoSection,
+    source: &Path,
+    files: &dyn Files,
+) -> Result<(), UnitLoadError> {
+    let dir = source.parent().unwrap_or(Path::new(""));
+    let mut from_files = HashMap::new();
+
+    for entry in &todo.env_file {
+        let (name, optional) = match entry.strip_prefix(OPTIONAL_PREFIX) {
+            Some(name) => (name, true),
+            None => (entry.as_str(), false),
+        };
+        let path = dir.join(name);
+
+        let contents = match files.read(&path) {
+            Ok(contents) => contents,
+            Err(e) if optional && e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(UnitLoadError::EnvFileError(source.to_path_buf(), path, e)),
+        };
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .filter(|(key, _)| !key.trim().is_empty())
+                .ok_or_else(|| UnitLoadError::InvalidEnvFileLine(path.clone(), number + 1))?;
+
+            from_files.insert(key.trim().to_string(), unquote(value.trim()));
+        }
+    }
+
+    for (key, value) in from_files {
+        todo.env.entry(key).or_insert(value);
+    }
+
+    Ok(())
+}
+
+fn unquote(value: &str) -> String {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(quote).and_then(|v| v.strip_suffix(quote)) {
+            return inner.to_string();
+        }
+    }
+    value.to_string()
+}
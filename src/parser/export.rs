@@ -0,0 +1,95 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use petgraph::visit::EdgeRef;
+
+use crate::parser::generator::GenerateOptions;
+use crate::parser::graph::UnitGraph;
+use crate::unit::errors::GraphBuildError;
+use crate::unit::types::{UnitFile, UnitType};
+
+/// Renders the ordering graph as a Graphviz digraph. Edges point in startup order and are
+/// labeled with the declaration (`before`/`after`) that produced them.
+pub fn to_dot(units: &[UnitFile]) -> Result<String, GraphBuildError> {
+    to_dot_with(units, &GenerateOptions::default())
+}
+
+/// Like `to_dot`, but builds the graph with `opts`, so injected `default_after` edges show up
+/// labeled `default`.
+pub fn to_dot_with(units: &[UnitFile], opts: &GenerateOptions) -> Result<String, GraphBuildError> {
+    let unit_graph = UnitGraph::build(units, opts)?;
+    let graph = unit_graph.inner();
+
+    let mut out = String::from("digraph units {\n");
+
+    for node in graph.node_indices() {
+        let color = match units[node.index()].unit.unit_type {
+            UnitType::Service => "lightblue",
+            UnitType::Target => "lightyellow",
+            UnitType::Timer => "lightgreen",
+            UnitType::Socket => "lightpink",
+        };
+        let _ = writeln!(
+            out,
+            "    \"{}\" [shape=box, style=filled, fillcolor={}];",
+            escape(&graph[node]),
+            color
+        );
+    }
+
+    for edge in graph.edge_references() {
+        let _ = writeln!(
+            out,
+            "    \"{}\" -> \"{}\" [label=\"{}\"];",
+            escape(&graph[edge.source()]),
+            escape(&graph[edge.target()]),
+            edge.weight().label()
+        );
+    }
+
+    out.push_str("}\n");
+    Ok(out)
+}
+
+/// Renders a Mermaid `graph TD` block with an edge from each unit to each of its
+/// dependencies. Services are drawn as rectangles, targets as hexagons, timers as circles and
+/// sockets as flags.
+pub fn to_mermaid(units: &[UnitFile]) -> Result<String, GraphBuildError> {
+    to_mermaid_with(units, &GenerateOptions::default())
+}
+
+/// Like `to_mermaid`, but builds the graph with `opts`.
+pub fn to_mermaid_with(
+    units: &[UnitFile],
+    opts: &GenerateOptions,
+) -> Result<String, GraphBuildError> {
+    let unit_graph = UnitGraph::build(units, opts)?;
+    let graph = unit_graph.inner();
+
+    let mut out = String::from("graph TD\n");
+
+    for node in graph.node_indices() {
+        let label = graph[node].replace('"', "#quot;");
+        let _ = match units[node.index()].unit.unit_type {
+            UnitType::Service => writeln!(out, "    n{}[\"{}\"]", node.index(), label),
+            UnitType::Target => writeln!(out, "    n{}{{{{\"{}\"}}}}", node.index(), label),
+            UnitType::Timer => writeln!(out, "    n{}((\"{}\"))", node.index(), label),
+            UnitType::Socket => writeln!(out, "    n{}>\"{}\"]", node.index(), label),
+        };
+    }
+
+    // A `before` and an `after` declaration can produce the same edge twice.
+    let edges: BTreeSet<_> = graph
+        .edge_references()
+        .map(|edge| (edge.target().index(), edge.source().index()))
+        .collect();
+    for (unit, dep) in edges {
+        let _ = writeln!(out, "    n{unit} --> n{dep}");
+    }
+
+    Ok(out)
+}
+
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
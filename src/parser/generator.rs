@@ -1,52 +1,408 @@
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
 
-use petgraph::algo::toposort;
-use petgraph::{Directed, Graph};
+use petgraph::Direction;
+use petgraph::algo::kosaraju_scc;
+use petgraph::graph::NodeIndex;
 
-use crate::unit::errors::{GraphBuildError, UnitLoadError};
-use crate::unit::types::UnitFile;
+use crate::parser::graph::{UnitGraph, cycle_path, node_names};
+use crate::parser::observer::ObserverHandle;
+use crate::unit::errors::GraphBuildError;
+use crate::unit::types::{UnitFile, UnitType};
 
+/// How to treat `before`, `after` and `requires` entries naming a unit that is not loaded.
+///
+/// `Error` is the default and what `generate` uses. `Record` drops the reference like
+/// `Ignore` but collects it, see `generate_with_missing`. `wants` entries are always soft:
+/// they warn under `Error` and `Warn`, are recorded under `Record` and are dropped silently
+/// under `Ignore`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MissingDepPolicy {
+    #[default]
+    Error,
+    Warn,
+    Record,
+    Ignore,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct GenerateOptions {
+    pub missing_dependency: MissingDepPolicy,
+
+    /// Units left out of the ordering as if they were not loaded, like systemd masking.
+    /// Ordering against a masked unit and wanting one are no-ops; requiring one is a
+    /// `MaskedDependency` error.
+    pub mask: HashSet<String>,
+
+    /// Let several units provide the same alias; references resolve to the first one.
+    pub allow_ambiguous_aliases: bool,
+
+    /// Match unit names, aliases, dependency references and `mask` entries ignoring ASCII and
+    /// Unicode case.
+    /// Two units whose names differ only in case are then reported as duplicates.
+    pub case_insensitive_names: bool,
+
+    /// Order every unit after this one unless it sets `default_dependencies = false`. Units
+    /// already ordered before it are left alone, so this never introduces a cycle.
+    pub default_after: Option<String>,
+
+    /// Let references omit or add the unit's type suffix, so `network` and `network.target`
+    /// name the same target. Two units that differ only by that suffix are an
+    /// `AmbiguousAlias` error.
+    pub suffix_insensitive: bool,
+
+    /// Report each `before`/`after` edge between units of different types as a
+    /// `CrossTypeOrdering` warning. Purely informational.
+    pub warn_cross_type_ordering: bool,
+
+    /// Fail with `DepthLimitExceeded` if a chain of units ordered one after another is longer
+    /// than this. Checked by every function that sorts the graph, including the `_with`
+    /// variants of `critical_path`, `generate_levels`, `transitive_dependencies` and
+    /// `explain_order`. All traversals are iterative, so this guards against runaway
+    /// generated configs rather than stack overflows.
+    pub max_depth: Option<usize>,
+
+    pub observer: ObserverHandle,
+}
+
+/// Units in startup order. The order is deterministic: units that are not ordered relative
+/// to each other, including units with no dependencies at all, come out sorted by name, and
+/// units sharing a name keep their input order.
 pub fn generate_unit_list(units: &[UnitFile]) -> Result<Vec<UnitFile>, GraphBuildError> {
-    let mut graph = Graph::<usize, (), Directed>::new();
-    let mut idx_map = HashMap::new();
+    generate_with(units, &GenerateOptions::default())
+}
+
+pub fn generate_with(
+    units: &[UnitFile],
+    opts: &GenerateOptions,
+) -> Result<Vec<UnitFile>, GraphBuildError> {
+    let order = order_with(units, opts)?;
+    Ok(order.into_iter().map(|idx| units[idx].clone()).collect())
+}
+
+/// `(unit, reference)` pairs for references to units that are not loaded.
+pub type MissingDependencies = Vec<(String, String)>;
+
+/// Like `generate_with`, but also returns the `(unit, reference)` pairs that named a unit
+/// that is not loaded. Set `missing_dependency` to `MissingDepPolicy::Record` to collect
+/// them instead of failing on the first one.
+pub fn generate_with_missing(
+    units: &[UnitFile],
+    opts: &GenerateOptions,
+) -> Result<(Vec<UnitFile>, MissingDependencies), GraphBuildError> {
+    let (unit_graph, sorted) = plan(units, opts)?;
+    let ordered = sorted.into_iter().map(|node| units[node.index()].clone()).collect();
+    Ok((ordered, unit_graph.missing_dependencies().to_vec()))
+}
+
+/// Like `generate`, but returns positions in `units` instead of cloning each unit.
+pub fn generate_order(units: &[UnitFile]) -> Result<Vec<usize>, GraphBuildError> {
+    order_with(units, &GenerateOptions::default())
+}
+
+fn order_with(units: &[UnitFile], opts: &GenerateOptions) -> Result<Vec<usize>, GraphBuildError> {
+    let (_, sorted) = plan(units, opts)?;
+    Ok(sorted.into_iter().map(NodeIndex::index).collect())
+}
+
+pub fn generate_shutdown_order(units: &[UnitFile]) -> Result<Vec<UnitFile>, GraphBuildError> {
+    let (_, sorted) = plan(units, &GenerateOptions::default())?;
+
+    let ordered_units =
+        sorted.into_iter().rev().map(|node_idx| units[node_idx.index()].clone()).collect();
+
+    Ok(ordered_units)
+}
+
+pub fn generate_levels(units: &[UnitFile]) -> Result<Vec<Vec<UnitFile>>, GraphBuildError> {
+    generate_levels_with(units, &GenerateOptions::default())
+}
+
+pub fn generate_levels_with(
+    units: &[UnitFile],
+    opts: &GenerateOptions,
+) -> Result<Vec<Vec<UnitFile>>, GraphBuildError> {
+    let (unit_graph, sorted) = plan(units, opts)?;
+    let graph = unit_graph.inner();
+
+    let mut level_of = HashMap::new();
+    let mut levels: Vec<Vec<UnitFile>> = Vec::new();
+
+    for node in sorted {
+        let level = graph
+            .neighbors_directed(node, Direction::Incoming)
+            .map(|pred| level_of[&pred] + 1)
+            .max()
+            .unwrap_or(0);
+        level_of.insert(node, level);
 
-    for (i, unit) in units.iter().enumerate() {
-        let node_idx = graph.add_node(i);
-        idx_map.insert(unit.unit.unit_name.clone(), node_idx);
+        if levels.len() <= level {
+            levels.resize_with(level + 1, Vec::new);
+        }
+        levels[level].push(units[node.index()].clone());
     }
 
-    for unit in units {
-        let from = *idx_map.get(&unit.unit.unit_name).unwrap();
+    Ok(levels)
+}
 
-        for dep in &unit.dependency.needs_before {
-            if let Some(&to) = idx_map.get(dep) {
-                graph.add_edge(from, to, ());
-            } else {
-                return Err(GraphBuildError::LoadError(UnitLoadError::MissingDependency(
-                    unit.unit.unit_name.clone(),
-                    dep.clone(),
-                )));
+/// The longest chain of units that have to start one after another, in startup order.
+pub fn critical_path(units: &[UnitFile]) -> Result<Vec<UnitFile>, GraphBuildError> {
+    critical_path_with(units, &GenerateOptions::default())
+}
+
+pub fn critical_path_with(
+    units: &[UnitFile],
+    opts: &GenerateOptions,
+) -> Result<Vec<UnitFile>, GraphBuildError> {
+    let (unit_graph, sorted) = plan(units, opts)?;
+    let graph = unit_graph.inner();
+
+    let mut length: HashMap<NodeIndex, usize> = HashMap::new();
+    let mut previous: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+    let mut end = None;
+
+    for &node in &sorted {
+        let longest = graph
+            .neighbors_directed(node, Direction::Incoming)
+            .max_by_key(|pred| (length[pred], Reverse(*pred)));
+        let len = match longest {
+            Some(pred) => {
+                previous.insert(node, pred);
+                length[&pred] + 1
             }
+            None => 1,
+        };
+        length.insert(node, len);
+
+        if end.is_none_or(|(best, _)| len > best) {
+            end = Some((len, node));
         }
-        for dep in &unit.dependency.needs_after {
-            if let Some(&to) = idx_map.get(dep) {
-                graph.add_edge(to, from, ());
-            } else {
-                return Err(GraphBuildError::LoadError(UnitLoadError::MissingDependency(
-                    unit.unit.unit_name.clone(),
-                    dep.clone(),
-                )));
+    }
+
+    let mut path = Vec::new();
+    let mut current = end.map(|(_, node)| node);
+    while let Some(node) = current {
+        path.push(units[node.index()].clone());
+        current = previous.get(&node).copied();
+    }
+    path.reverse();
+
+    Ok(path)
+}
+
+/// The shortest chain of ordering edges that makes `a` start before `b`, from `a` to `b`
+/// inclusive, or `None` if nothing orders `a` before `b`.
+pub fn explain_order(
+    units: &[UnitFile],
+    a: &str,
+    b: &str,
+) -> Result<Option<Vec<String>>, GraphBuildError> {
+    explain_order_with(units, a, b, &GenerateOptions::default())
+}
+
+/// Like `explain_order`, but builds the graph under `opts`. With `max_depth` set, the whole
+/// graph is checked against it first, which also rejects cycles.
+pub fn explain_order_with(
+    units: &[UnitFile],
+    a: &str,
+    b: &str,
+    opts: &GenerateOptions,
+) -> Result<Option<Vec<String>>, GraphBuildError> {
+    let unit_graph = UnitGraph::build(units, opts)?;
+    if opts.max_depth.is_some() {
+        unit_graph.sorted_nodes()?;
+    }
+    let node = |name: &str| {
+        unit_graph.node(name).ok_or_else(|| GraphBuildError::UnknownUnit(name.to_string()))
+    };
+    let (start, end) = (node(a)?, node(b)?);
+    if start == end {
+        return Ok(None);
+    }
+    let graph = unit_graph.inner();
+
+    let mut previous = HashMap::from([(start, start)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(current) = queue.pop_front() {
+        if current == end {
+            break;
+        }
+        for next in graph.neighbors(current) {
+            if let Entry::Vacant(entry) = previous.entry(next) {
+                entry.insert(current);
+                queue.push_back(next);
             }
         }
     }
 
-    let sorted = toposort(&graph, None).map_err(|cycle| {
-        let node = cycle.node_id();
-        let name = units[graph[node]].unit.unit_name.clone();
-        GraphBuildError::DependencyCycle(name)
-    })?;
+    if !previous.contains_key(&end) {
+        return Ok(None);
+    }
+
+    let mut path = vec![end];
+    let mut current = end;
+    while current != start {
+        current = previous[&current];
+        path.push(current);
+    }
+    path.reverse();
+    Ok(Some(node_names(graph, path)))
+}
+
+pub fn transitive_dependencies(
+    units: &[UnitFile],
+    root: &str,
+) -> Result<Vec<UnitFile>, GraphBuildError> {
+    transitive_dependencies_with(units, root, &GenerateOptions::default())
+}
+
+pub fn transitive_dependencies_with(
+    units: &[UnitFile],
+    root: &str,
+    opts: &GenerateOptions,
+) -> Result<Vec<UnitFile>, GraphBuildError> {
+    let unit_graph = UnitGraph::build(units, opts)?;
+    let root_node =
+        unit_graph.node(root).ok_or_else(|| GraphBuildError::UnknownUnit(root.to_string()))?;
+    plan_closure(units, &unit_graph, root_node, opts)
+}
+
+/// Like `generate`, but only orders the units the target `target_name` transitively needs.
+pub fn generate_for_target(
+    units: &[UnitFile],
+    target_name: &str,
+) -> Result<Vec<UnitFile>, GraphBuildError> {
+    let unit_graph = UnitGraph::from_units(units)?;
+    let target_node = unit_graph
+        .node(target_name)
+        .ok_or_else(|| GraphBuildError::UnknownUnit(target_name.to_string()))?;
+
+    if units[target_node.index()].unit.unit_type != UnitType::Target {
+        return Err(GraphBuildError::NotATarget(target_name.to_string()));
+    }
+
+    plan_closure(units, &unit_graph, target_node, &GenerateOptions::default())
+}
+
+/// Names of the units, in input order, that `target` does not transitively need and that
+/// would therefore never be started through it.
+pub fn unreachable_from(units: &[UnitFile], target: &str) -> Result<Vec<String>, GraphBuildError> {
+    let unit_graph = UnitGraph::from_units(units)?;
+    let target_node =
+        unit_graph.node(target).ok_or_else(|| GraphBuildError::UnknownUnit(target.to_string()))?;
+
+    if units[target_node.index()].unit.unit_type != UnitType::Target {
+        return Err(GraphBuildError::NotATarget(target.to_string()));
+    }
+
+    let needed = unit_graph.closure(target_node);
+    let unreachable = unit_graph
+        .inner()
+        .node_indices()
+        .filter(|node| !needed.contains(node))
+        .map(|node| units[node.index()].unit.unit_name.clone())
+        .collect();
+
+    Ok(unreachable)
+}
+
+fn plan_closure(
+    units: &[UnitFile],
+    unit_graph: &UnitGraph,
+    root: NodeIndex,
+    opts: &GenerateOptions,
+) -> Result<Vec<UnitFile>, GraphBuildError> {
+    let mut needed = unit_graph.closure(root);
+    needed.retain(|node| !unit_graph.is_masked(&units[node.index()].unit.unit_name));
+
+    find_conflict(needed.iter().map(|node| &units[node.index()]), opts.case_insensitive_names)?;
+    let sorted = unit_graph.sorted_nodes()?;
 
-    let ordered_units = sorted.into_iter().map(|node_idx| units[graph[node_idx]].clone()).collect();
+    let ordered_units = sorted
+        .into_iter()
+        .filter(|node| needed.contains(node))
+        .map(|node| units[node.index()].clone())
+        .collect();
 
     Ok(ordered_units)
 }
+
+pub fn find_cycles(units: &[UnitFile]) -> Result<Vec<Vec<String>>, GraphBuildError> {
+    let unit_graph = UnitGraph::from_units(units)?;
+    let graph = unit_graph.inner();
+
+    let cycles = kosaraju_scc(graph)
+        .into_iter()
+        .filter(|scc| scc.len() > 1)
+        .map(|scc| node_names(graph, cycle_path(graph, &scc)))
+        .collect();
+
+    Ok(cycles)
+}
+
+/// Units nothing is ordered after, i.e. the entry points of the graph.
+pub fn roots(units: &[UnitFile]) -> Result<Vec<&UnitFile>, GraphBuildError> {
+    units_without_edges(units, Direction::Outgoing)
+}
+
+/// Units that are not ordered after anything.
+pub fn leaves(units: &[UnitFile]) -> Result<Vec<&UnitFile>, GraphBuildError> {
+    units_without_edges(units, Direction::Incoming)
+}
+
+fn units_without_edges(
+    units: &[UnitFile],
+    direction: Direction,
+) -> Result<Vec<&UnitFile>, GraphBuildError> {
+    let unit_graph = UnitGraph::from_units(units)?;
+    let graph = unit_graph.inner();
+
+    Ok(graph
+        .node_indices()
+        .filter(|&node| graph.neighbors_directed(node, direction).next().is_none())
+        .map(|node| &units[node.index()])
+        .collect())
+}
+
+pub fn check_conflicts(units: &[UnitFile]) -> Result<(), GraphBuildError> {
+    find_conflict(units.iter(), false)
+}
+
+fn find_conflict<'a>(
+    units: impl Iterator<Item = &'a UnitFile> + Clone,
+    case_insensitive: bool,
+) -> Result<(), GraphBuildError> {
+    let key = |name: &str| if case_insensitive { name.to_lowercase() } else { name.to_string() };
+    let own_names = |u: &'a UnitFile| std::iter::once(&u.unit.unit_name).chain(&u.unit.provides);
+    let names: HashSet<String> = units.clone().flat_map(own_names).map(|n| key(n)).collect();
+
+    for unit in units {
+        for other in &unit.dependency.conflicts {
+            let other_key = key(other);
+            let own = own_names(unit).any(|name| key(name) == other_key);
+            if !own && names.contains(&other_key) {
+                return Err(GraphBuildError::ConflictViolation(
+                    unit.unit.unit_name.clone(),
+                    other.clone(),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn plan(
+    units: &[UnitFile],
+    opts: &GenerateOptions,
+) -> Result<(UnitGraph, Vec<NodeIndex>), GraphBuildError> {
+    let unit_graph = UnitGraph::build(units, opts)?;
+    let masked = |unit: &UnitFile| unit_graph.is_masked(&unit.unit.unit_name);
+    find_conflict(units.iter().filter(|unit| !masked(unit)), opts.case_insensitive_names)?;
+
+    let mut sorted = unit_graph.sorted_nodes()?;
+    sorted.retain(|node| !masked(&units[node.index()]));
+    Ok((unit_graph, sorted))
+}
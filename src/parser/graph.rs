@@ -0,0 +1,730 @@
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::path::Path;
+
+use petgraph::algo::{has_path_connecting, kosaraju_scc};
+use petgraph::graph::EdgeReference;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use petgraph::{Directed, Direction, Graph};
+
+use crate::parser::generator::{GenerateOptions, MissingDepPolicy};
+use crate::parser::observer::{LoadWarning, ObserverHandle};
+use crate::unit::errors::{GraphBuildError, UnitLoadError};
+use crate::unit::types::{UnitFile, UnitType, is_wildcard};
+
+/// Which declaration produced an ordering edge `a -> b`: `a`'s `before` list, `b`'s `after`
+/// list, `a` being the socket that activates service `b`, `a` being installed into target `b`,
+/// or `a` being the `GenerateOptions::default_after` unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EdgeKind {
+    Before,
+    After,
+    Socket,
+    Install,
+    Default,
+}
+
+impl EdgeKind {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            EdgeKind::Before => "before",
+            EdgeKind::After => "after",
+            EdgeKind::Socket => "socket",
+            EdgeKind::Install => "install",
+            EdgeKind::Default => "default",
+        }
+    }
+}
+
+pub(crate) type DependencyGraph = Graph<String, EdgeKind, Directed>;
+
+/// Ordering graph over a set of units. An edge `a -> b` means `a` starts before `b`.
+///
+/// Nodes are added in input order, so a node's index equals the position of its unit in the
+/// slice the graph was built from.
+#[derive(Debug, Clone)]
+pub struct UnitGraph {
+    graph: DependencyGraph,
+    index: HashMap<String, NodeIndex>,
+    requirements: Vec<Vec<NodeIndex>>,
+    types: Vec<UnitType>,
+    /// `GenerateOptions::mask`, normalized like the keys of `index`.
+    mask: HashSet<String>,
+    missing: Vec<(String, String)>,
+    /// The options the graph was built with, reused by `insert_unit`.
+    opts: GenerateOptions,
+}
+
+impl UnitGraph {
+    pub fn from_units(units: &[UnitFile]) -> Result<Self, GraphBuildError> {
+        Self::build(units, &GenerateOptions::default())
+    }
+
+    /// Like `from_units`, but links units the way `generate_with` does under `opts`. The
+    /// options are kept for `insert_unit`.
+    pub fn from_units_with(
+        units: &[UnitFile],
+        opts: &GenerateOptions,
+    ) -> Result<Self, GraphBuildError> {
+        Self::build(units, opts)
+    }
+
+    pub(crate) fn build(
+        units: &[UnitFile],
+        opts: &GenerateOptions,
+    ) -> Result<Self, GraphBuildError> {
+        let mut unit_graph = UnitGraph {
+            graph: Graph::new(),
+            index: HashMap::new(),
+            requirements: Vec::new(),
+            types: Vec::new(),
+            mask: opts
+                .mask
+                .iter()
+                .map(|name| normalize(name, opts.case_insensitive_names).into_owned())
+                .collect(),
+            missing: Vec::new(),
+            opts: opts.clone(),
+        };
+
+        for (i, unit) in units.iter().enumerate() {
+            if let Some(first) = unit_graph.add_unit(unit)
+                && opts.case_insensitive_names
+            {
+                let origin =
+                    |unit: &UnitFile| unit.origin().map(Path::to_path_buf).unwrap_or_default();
+                return Err(GraphBuildError::LoadError(UnitLoadError::DuplicateUnitName(
+                    unit.unit.unit_name.clone(),
+                    origin(&units[first.index()]),
+                    origin(&units[i]),
+                )));
+            }
+        }
+
+        // Masked units keep their aliases so references through them are recognized as
+        // masked, but any unmasked provider of the same alias wins.
+        let (masked, live): (Vec<_>, Vec<_>) = (0..units.len())
+            .map(NodeIndex::new)
+            .partition(|&node| unit_graph.is_masked(&unit_graph.graph[node]));
+        for &node in live.iter().chain(&masked) {
+            unit_graph.add_aliases(node, &units[node.index()])?;
+        }
+
+        for &node in &live {
+            unit_graph.link_dependencies(node, &units[node.index()])?;
+        }
+        for &node in &live {
+            unit_graph.link_install(node, &units[node.index()])?;
+        }
+        if let Some(base) = unit_graph.default_base() {
+            for &node in &live {
+                unit_graph.link_default(base, node, &units[node.index()])?;
+            }
+        }
+        for &node in &live {
+            unit_graph.check_requires_conflicts(&units[node.index()])?;
+        }
+
+        if opts.warn_cross_type_ordering {
+            unit_graph.warn_cross_type_ordering(unit_graph.graph.edge_references());
+        }
+
+        Ok(unit_graph)
+    }
+
+    /// Adds `unit` as a new node after the existing ones, so `topo_order` includes it without
+    /// rebuilding the graph. It is linked exactly as `generate_with` would link it, using
+    /// the options the graph was built with. References to it from units already in the
+    /// graph, including `default_after` when it is the new unit, are not resolved: those
+    /// were dropped when the graph was built. Only cycles through the new unit are looked
+    /// for. On error the graph is left unchanged.
+    pub fn insert_unit(&mut self, unit: UnitFile) -> Result<(), GraphBuildError> {
+        let name = &unit.unit.unit_name;
+        if let Some(taken) =
+            std::iter::once(name).chain(&unit.unit.provides).find(|n| self.contains(n))
+        {
+            return Err(GraphBuildError::UnitExists(taken.clone()));
+        }
+
+        let missing = self.missing.len();
+        self.add_unit(&unit);
+        let node = NodeIndex::new(self.graph.node_count() - 1);
+        if let Err(e) = self.link_inserted(node, &unit) {
+            self.graph.remove_node(node);
+            self.requirements.pop();
+            self.types.pop();
+            self.index.retain(|_, indexed| *indexed != node);
+            for requirements in &mut self.requirements {
+                requirements.retain(|required| *required != node);
+            }
+            self.missing.truncate(missing);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn link_inserted(&mut self, node: NodeIndex, unit: &UnitFile) -> Result<(), GraphBuildError> {
+        self.add_aliases(node, unit)?;
+        if self.is_masked(&unit.unit.unit_name) {
+            return Ok(());
+        }
+
+        self.link_dependencies(node, unit)?;
+        self.link_install(node, unit)?;
+        if let Some(base) = self.default_base() {
+            self.link_default(base, node, unit)?;
+        }
+        self.check_requires_conflicts(unit)?;
+
+        let closes_cycle = self
+            .graph
+            .neighbors(node)
+            .any(|next| has_path_connecting(&self.graph, next, node, None));
+        if closes_cycle {
+            let scc =
+                kosaraju_scc(&self.graph).into_iter().find(|scc| scc.contains(&node)).unwrap();
+            return Err(GraphBuildError::DependencyCycle(node_names(
+                &self.graph,
+                cycle_path(&self.graph, &scc),
+            )));
+        }
+
+        if self.opts.warn_cross_type_ordering {
+            let graph = &self.graph;
+            self.warn_cross_type_ordering(
+                graph
+                    .edges_directed(node, Direction::Outgoing)
+                    .chain(graph.edges_directed(node, Direction::Incoming)),
+            );
+        }
+        Ok(())
+    }
+
+    /// Adds a node for `unit` and indexes it by name. Returns the node that name previously
+    /// resolved to, if any.
+    fn add_unit(&mut self, unit: &UnitFile) -> Option<NodeIndex> {
+        let node = self.graph.add_node(unit.unit.unit_name.clone());
+        self.requirements.push(Vec::new());
+        self.types.push(unit.unit.unit_type);
+        let key = self.key(&unit.unit.unit_name).into_owned();
+        self.index.insert(key, node)
+    }
+
+    fn add_aliases(&mut self, node: NodeIndex, unit: &UnitFile) -> Result<(), GraphBuildError> {
+        let allow_ambiguous =
+            self.opts.allow_ambiguous_aliases || self.is_masked(&unit.unit.unit_name);
+        if self.opts.suffix_insensitive {
+            let suffix = format!(".{}", unit.unit.unit_type);
+            let name = &unit.unit.unit_name;
+            let alias = match name.strip_suffix(&suffix) {
+                Some(stem) => stem.to_string(),
+                None => format!("{name}{suffix}"),
+            };
+            self.add_alias(&alias, node, allow_ambiguous)?;
+        }
+        for alias in &unit.unit.provides {
+            self.add_alias(alias, node, allow_ambiguous)?;
+        }
+        Ok(())
+    }
+
+    /// Adds the edges and requirements from `unit`'s `[dependency]` section and socket.
+    fn link_dependencies(
+        &mut self,
+        from: NodeIndex,
+        unit: &UnitFile,
+    ) -> Result<(), GraphBuildError> {
+        let name = &unit.unit.unit_name;
+        warn_duplicates(unit, &self.opts.observer);
+
+        for dep in &unit.dependency.needs_before {
+            for to in self.ordering_targets(from, dep)? {
+                self.add_ordering(from, to, EdgeKind::Before)?;
+            }
+        }
+        for dep in &unit.dependency.needs_after {
+            for to in self.ordering_targets(from, dep)? {
+                self.add_ordering(to, from, EdgeKind::After)?;
+            }
+        }
+        if let Some(socket) = unit.socket.as_ref().filter(|s| !self.masks(&s.service)) {
+            match self.node(&socket.service) {
+                Some(to) => self.add_ordering(from, to, EdgeKind::Socket)?,
+                None => self.missing_dependency(name, &socket.service)?,
+            }
+        }
+        for dep in &unit.dependency.requires {
+            if self.masks(dep) {
+                let masked = self.node(dep).map(|node| self.graph[node].clone());
+                return Err(GraphBuildError::MaskedDependency(
+                    name.clone(),
+                    masked.unwrap_or_else(|| dep.clone()),
+                ));
+            }
+            match self.node(dep) {
+                Some(to) => self.add_requirement(from, to),
+                None => self.missing_dependency(name, dep)?,
+            }
+        }
+        for dep in &unit.dependency.wants {
+            if self.masks(dep) {
+                continue;
+            }
+            match self.node(dep) {
+                Some(to) => self.add_requirement(from, to),
+                None => self.missing_soft(
+                    name,
+                    dep,
+                    LoadWarning::MissingWant { unit: name.clone(), dependency: dep.clone() },
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    /// Links `unit` into the targets of its `[install]` section.
+    fn link_install(&mut self, node: NodeIndex, unit: &UnitFile) -> Result<(), GraphBuildError> {
+        let name = &unit.unit.unit_name;
+        for target in &unit.install.wanted_by {
+            if self.masks(target) {
+                continue;
+            }
+            match self.node(target) {
+                Some(target) => self.add_install(node, target)?,
+                None => self.missing_soft(
+                    name,
+                    target,
+                    LoadWarning::MissingInstallTarget {
+                        unit: name.clone(),
+                        target: target.clone(),
+                    },
+                ),
+            }
+        }
+        for target in &unit.install.required_by {
+            if self.masks(target) {
+                continue;
+            }
+            match self.node(target) {
+                Some(target) => self.add_install(node, target)?,
+                None => self.missing_dependency(name, target)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// The `default_after` unit, unless it is masked or not loaded.
+    fn default_base(&self) -> Option<NodeIndex> {
+        let base = self.opts.default_after.as_ref().filter(|base| !self.masks(base))?;
+        self.node(base)
+    }
+
+    fn link_default(
+        &mut self,
+        base: NodeIndex,
+        node: NodeIndex,
+        unit: &UnitFile,
+    ) -> Result<(), GraphBuildError> {
+        if node == base
+            || !unit.unit.default_dependencies
+            || has_path_connecting(&self.graph, node, base, None)
+        {
+            return Ok(());
+        }
+        self.add_ordering(base, node, EdgeKind::Default)
+    }
+
+    /// `(unit, reference)` pairs naming units that were not loaded, collected under
+    /// `MissingDepPolicy::Record`.
+    pub fn missing_dependencies(&self) -> &[(String, String)] {
+        &self.missing
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.node(name).is_some()
+    }
+
+    /// Units that must start before `name`.
+    pub fn dependencies_of(&self, name: &str) -> Vec<&str> {
+        self.neighbors(name, Direction::Incoming)
+    }
+
+    /// Units that must start after `name`.
+    pub fn dependents_of(&self, name: &str) -> Vec<&str> {
+        self.neighbors(name, Direction::Outgoing)
+    }
+
+    pub(crate) fn inner(&self) -> &DependencyGraph {
+        &self.graph
+    }
+
+    pub(crate) fn node(&self, name: &str) -> Option<NodeIndex> {
+        self.index.get(self.key(name).as_ref()).copied()
+    }
+
+    fn key<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        normalize(name, self.opts.case_insensitive_names)
+    }
+
+    /// Whether `name` is a masked unit name, compared the way names are resolved.
+    pub(crate) fn is_masked(&self, name: &str) -> bool {
+        self.mask.contains(&*self.key(name))
+    }
+
+    /// Whether the reference `dep` resolves to a masked unit, by name or through an alias. A
+    /// reference to no loaded unit counts as masked when the mask lists it.
+    fn masks(&self, dep: &str) -> bool {
+        match self.node(dep) {
+            Some(node) => self.is_masked(&self.graph[node]),
+            None => self.is_masked(dep),
+        }
+    }
+
+    /// Every node `root` needs: units ordered before it and units it requires or wants,
+    /// followed transitively. Includes `root` itself.
+    pub(crate) fn closure(&self, root: NodeIndex) -> HashSet<NodeIndex> {
+        let mut reached = HashSet::from([root]);
+        let mut stack = vec![root];
+
+        while let Some(node) = stack.pop() {
+            let needed = self
+                .graph
+                .neighbors_directed(node, Direction::Incoming)
+                .chain(self.requirements[node.index()].iter().copied());
+            for next in needed {
+                if reached.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+
+        reached
+    }
+
+    /// Positions of the units in startup order, ties broken by name and then by position.
+    /// Fails with `DependencyCycle` if the ordering edges form a cycle. Conflicts and masking
+    /// are not taken into account here; `generate` checks those before sorting.
+    pub fn topo_order(&self) -> Result<Vec<usize>, GraphBuildError> {
+        Ok(self.sorted_nodes()?.into_iter().map(NodeIndex::index).collect())
+    }
+
+    pub(crate) fn sorted_nodes(&self) -> Result<Vec<NodeIndex>, GraphBuildError> {
+        let graph = &self.graph;
+        let name = |node: NodeIndex| graph[node].as_str();
+
+        let mut in_degree: HashMap<NodeIndex, usize> = graph
+            .node_indices()
+            .map(|node| (node, graph.neighbors_directed(node, Direction::Incoming).count()))
+            .collect();
+
+        let mut ready: BinaryHeap<Reverse<(&str, NodeIndex)>> = in_degree
+            .iter()
+            .filter(|&(_, &degree)| degree == 0)
+            .map(|(&node, _)| Reverse((name(node), node)))
+            .collect();
+
+        let mut sorted = Vec::with_capacity(graph.node_count());
+
+        while let Some(Reverse((_, node))) = ready.pop() {
+            sorted.push(node);
+            for next in graph.neighbors(node) {
+                let degree = in_degree.get_mut(&next).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push(Reverse((name(next), next)));
+                }
+            }
+        }
+
+        if sorted.len() < graph.node_count() {
+            let scc = kosaraju_scc(graph)
+                .into_iter()
+                .find(|scc| scc.len() > 1 || graph.contains_edge(scc[0], scc[0]))
+                .unwrap();
+            let path = cycle_path(graph, &scc);
+            return Err(GraphBuildError::DependencyCycle(node_names(graph, path)));
+        }
+
+        if let Some(limit) = self.opts.max_depth {
+            self.check_depth(&sorted, limit)?;
+        }
+        Ok(sorted)
+    }
+
+    /// Fails if any chain of ordering edges holds more than `limit` units. `sorted` must be in
+    /// topological order, so one pass suffices.
+    fn check_depth(&self, sorted: &[NodeIndex], limit: usize) -> Result<(), GraphBuildError> {
+        let mut depth: HashMap<NodeIndex, usize> = HashMap::with_capacity(sorted.len());
+
+        for &node in sorted {
+            let len = self
+                .graph
+                .neighbors_directed(node, Direction::Incoming)
+                .map(|pred| depth[&pred])
+                .max()
+                .map_or(1, |len| len + 1);
+            if len > limit {
+                return Err(GraphBuildError::DepthLimitExceeded(self.graph[node].clone(), limit));
+            }
+            depth.insert(node, len);
+        }
+        Ok(())
+    }
+
+    fn neighbors(&self, name: &str, direction: Direction) -> Vec<&str> {
+        let Some(node) = self.node(name) else {
+            return Vec::new();
+        };
+
+        let mut names: Vec<&str> = self
+            .graph
+            .neighbors_directed(node, direction)
+            .map(|other| self.graph[other].as_str())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Warns about each of `edges` that is a `before`/`after` edge between units of different
+    /// types. Socket, install and default edges are cross-type by design and left out.
+    fn warn_cross_type_ordering<'a>(
+        &self,
+        edges: impl Iterator<Item = EdgeReference<'a, EdgeKind>>,
+    ) {
+        for edge in edges {
+            if !matches!(edge.weight(), EdgeKind::Before | EdgeKind::After) {
+                continue;
+            }
+            let (first, then) = (edge.source(), edge.target());
+            let (before_type, after_type) = (self.types[first.index()], self.types[then.index()]);
+            if before_type != after_type {
+                self.opts.observer.warn(LoadWarning::CrossTypeOrdering {
+                    before: self.graph[first].clone(),
+                    before_type,
+                    after: self.graph[then].clone(),
+                    after_type,
+                });
+            }
+        }
+    }
+
+    /// The nodes a `before` or `after` entry of the unit at `node` refers to: every unmasked
+    /// match of a wildcard, or the named unit unless it is masked.
+    fn ordering_targets(
+        &mut self,
+        node: NodeIndex,
+        dep: &str,
+    ) -> Result<Vec<NodeIndex>, GraphBuildError> {
+        if self.masks(dep) {
+            return Ok(Vec::new());
+        }
+        if is_wildcard(dep) {
+            let mut matches = self.matching(dep, node)?;
+            matches.retain(|node| !self.is_masked(&self.graph[*node]));
+            if matches.is_empty() && self.opts.missing_dependency != MissingDepPolicy::Ignore {
+                self.opts.observer.warn(LoadWarning::UnmatchedPattern {
+                    unit: self.graph[node].clone(),
+                    pattern: dep.to_string(),
+                });
+            }
+            return Ok(matches);
+        }
+        match self.node(dep) {
+            Some(to) => Ok(vec![to]),
+            None => {
+                let name = self.graph[node].clone();
+                self.missing_dependency(&name, dep)?;
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// Nodes other than `from` whose unit name matches the wildcard `pattern`, with or without
+    /// the type suffix, so `*.service` covers every service. Aliases are never matched.
+    fn matching(&self, pattern: &str, from: NodeIndex) -> Result<Vec<NodeIndex>, GraphBuildError> {
+        let glob = glob::Pattern::new(pattern)
+            .map_err(|e| UnitLoadError::InvalidGlobPattern(pattern.to_string(), e))?;
+        Ok(self
+            .graph
+            .node_indices()
+            .filter(|&node| node != from)
+            .filter(|&node| {
+                let name = &self.graph[node];
+                let suffixed = format!("{name}.{}", self.types[node.index()]);
+                glob.matches(name) || glob.matches(&suffixed)
+            })
+            .collect())
+    }
+
+    /// Catches a unit requiring and conflicting with the same unit under different names, e.g.
+    /// requiring an alias of a unit it conflicts with. `validate` already rejects the
+    /// same name appearing in both lists.
+    fn check_requires_conflicts(&self, unit: &UnitFile) -> Result<(), GraphBuildError> {
+        let required: HashSet<NodeIndex> =
+            unit.dependency.requires.iter().filter_map(|dep| self.node(dep)).collect();
+
+        for other in &unit.dependency.conflicts {
+            if let Some(node) = self.node(other)
+                && required.contains(&node)
+            {
+                return Err(GraphBuildError::RequiresAndConflicts(
+                    unit.unit.unit_name.clone(),
+                    self.graph[node].clone(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Makes `alias` resolve to `node`. Unit names are registered first and always win over
+    /// an alias; two units providing the same alias is an error unless `allow_ambiguous` is
+    /// set, in which case the first provider keeps it.
+    fn add_alias(
+        &mut self,
+        alias: &str,
+        node: NodeIndex,
+        allow_ambiguous: bool,
+    ) -> Result<(), GraphBuildError> {
+        match self.node(alias) {
+            None => {
+                let key = self.key(alias).into_owned();
+                self.index.insert(key, node);
+            }
+            Some(existing) if existing == node || allow_ambiguous => {}
+            Some(existing) => {
+                return Err(GraphBuildError::AmbiguousAlias(
+                    alias.to_string(),
+                    self.graph[existing].clone(),
+                    self.graph[node].clone(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn add_ordering(
+        &mut self,
+        from: NodeIndex,
+        to: NodeIndex,
+        kind: EdgeKind,
+    ) -> Result<(), GraphBuildError> {
+        if from != to && self.graph.contains_edge(to, from) {
+            return Err(GraphBuildError::ContradictoryOrdering(
+                self.graph[from].clone(),
+                self.graph[to].clone(),
+            ));
+        }
+
+        if !self.graph.contains_edge(from, to) {
+            self.graph.add_edge(from, to, kind);
+        }
+        Ok(())
+    }
+
+    /// `target` needs `unit` and, unless `target` is already ordered before it, starts after it.
+    fn add_install(&mut self, unit: NodeIndex, target: NodeIndex) -> Result<(), GraphBuildError> {
+        self.add_requirement(target, unit);
+        if unit == target || has_path_connecting(&self.graph, target, unit, None) {
+            return Ok(());
+        }
+        self.add_ordering(unit, target, EdgeKind::Install)
+    }
+
+    fn missing_dependency(&mut self, unit: &str, dep: &str) -> Result<(), GraphBuildError> {
+        match self.opts.missing_dependency {
+            MissingDepPolicy::Error => Err(GraphBuildError::LoadError(
+                UnitLoadError::MissingDependency(unit.to_string(), dep.to_string()),
+            )),
+            MissingDepPolicy::Warn => {
+                self.opts.observer.warn(LoadWarning::MissingDependency {
+                    unit: unit.to_string(),
+                    dependency: dep.to_string(),
+                });
+                Ok(())
+            }
+            MissingDepPolicy::Record => {
+                self.missing.push((unit.to_string(), dep.to_string()));
+                Ok(())
+            }
+            MissingDepPolicy::Ignore => Ok(()),
+        }
+    }
+
+    /// Handles a missing `wants` or `wanted_by` entry, which never fails: it is recorded
+    /// under `Record`, dropped under `Ignore` and reported as `warning` otherwise.
+    fn missing_soft(&mut self, unit: &str, dep: &str, warning: LoadWarning) {
+        match self.opts.missing_dependency {
+            MissingDepPolicy::Record => self.missing.push((unit.to_string(), dep.to_string())),
+            MissingDepPolicy::Ignore => {}
+            MissingDepPolicy::Error | MissingDepPolicy::Warn => self.opts.observer.warn(warning),
+        }
+    }
+
+    fn add_requirement(&mut self, from: NodeIndex, to: NodeIndex) {
+        let requirements = &mut self.requirements[from.index()];
+        if !requirements.contains(&to) {
+            requirements.push(to);
+        }
+    }
+}
+
+fn normalize(name: &str, case_insensitive: bool) -> Cow<'_, str> {
+    if case_insensitive { Cow::Owned(name.to_lowercase()) } else { Cow::Borrowed(name) }
+}
+
+fn warn_duplicates(unit: &UnitFile, observer: &ObserverHandle) {
+    let dependency = &unit.dependency;
+    for list in [
+        &dependency.needs_before,
+        &dependency.needs_after,
+        &dependency.requires,
+        &dependency.wants,
+        &dependency.conflicts,
+    ] {
+        let mut seen = HashSet::new();
+        for dep in list {
+            if !seen.insert(dep) {
+                observer.warn(LoadWarning::DuplicateDependency {
+                    unit: unit.unit.unit_name.clone(),
+                    dependency: dep.clone(),
+                });
+            }
+        }
+    }
+}
+
+pub(crate) fn node_names(graph: &DependencyGraph, nodes: Vec<NodeIndex>) -> Vec<String> {
+    nodes.into_iter().map(|node| graph[node].clone()).collect()
+}
+
+pub(crate) fn cycle_path(graph: &DependencyGraph, scc: &[NodeIndex]) -> Vec<NodeIndex> {
+    let start = scc[0];
+    let component: HashSet<NodeIndex> = scc.iter().copied().collect();
+
+    let mut parent = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        for next in graph.neighbors(node) {
+            if next == start {
+                let mut path = vec![node];
+                let mut current = node;
+                while let Some(&prev) = parent.get(&current) {
+                    path.push(prev);
+                    current = prev;
+                }
+                path.reverse();
+                return path;
+            }
+            if component.contains(&next) && !parent.contains_key(&next) {
+                parent.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    vec![start]
+}
@@ -0,0 +1,78 @@
+use std::path::{Path, PathBuf};
+
+use toml::{Table, Value};
+
+use crate::parser::loader::Files;
+use crate::unit::errors::UnitLoadError;
+
+const INCLUDE_KEY: &str = "include";
+
+/// Resolves the top-level `include = [...]` list of a TOML unit. Included files are
+/// resolved relative to the including file, merged in order, and the including file is merged
+/// on top, so its own values win. Tables are merged key by key; any other value is replaced.
+/// Returns `None` when the unit has no includes, leaving `contents` to be parsed as is.
+pub(crate) fn resolve_includes(
+    path: &Path,
+    contents: &str,
+    files: &dyn Files,
+) -> Result<Option<String>, UnitLoadError> {
+    let table: Table =
+        toml::from_str(contents).map_err(|e| UnitLoadError::ParseError(path.to_path_buf(), e))?;
+    if !table.contains_key(INCLUDE_KEY) {
+        return Ok(None);
+    }
+
+    let mut stack = vec![files.canonical(path)];
+    let resolved = expand(path, table, &mut stack, files)?;
+    Ok(Some(resolved.to_string()))
+}
+
+fn expand(
+    path: &Path,
+    mut table: Table,
+    stack: &mut Vec<PathBuf>,
+    files: &dyn Files,
+) -> Result<Table, UnitLoadError> {
+    let Some(includes) = table.remove(INCLUDE_KEY) else {
+        return Ok(table);
+    };
+    let includes: Vec<String> =
+        includes.try_into().map_err(|e| UnitLoadError::ParseError(path.to_path_buf(), e))?;
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut merged = Table::new();
+    for include in includes {
+        let included = dir.join(include);
+        let key = files.canonical(&included);
+        if let Some(start) = stack.iter().position(|p| *p == key) {
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(key);
+            return Err(UnitLoadError::IncludeCycle(cycle));
+        }
+
+        let contents =
+            files.read(&included).map_err(|e| UnitLoadError::ReadError(included.clone(), e))?;
+        let child: Table = toml::from_str(&contents)
+            .map_err(|e| UnitLoadError::ParseError(included.clone(), e))?;
+
+        stack.push(key);
+        let child = expand(&included, child, stack, files)?;
+        stack.pop();
+
+        merge_tables(&mut merged, child);
+    }
+
+    merge_tables(&mut merged, table);
+    Ok(merged)
+}
+
+fn merge_tables(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(existing)), Value::Table(value)) => merge_tables(existing, value),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::str::FromStr;
+
+use crate::unit::command::split_command;
+use crate::unit::duration::parse_duration;
+use crate::unit::types::{
+    ConditionSection, DependencySection, ExecCommand, InstallSection, RestartPolicy,
+    ServiceSection, SocketSection, TargetSection, TimerSection, TodoSection, UnitFile, UnitSection,
+    UnitType,
+};
+
+const SYSTEMD_SECTIONS: [&str; 5] = ["[Unit]", "[Service]", "[Timer]", "[Socket]", "[Install]"];
+
+/// systemd section headers are capitalized, ours are lowercase, so a capitalized header is
+/// enough to tell the two formats apart.
+pub(crate) fn is_systemd_unit(contents: &str) -> bool {
+    contents.lines().any(|line| SYSTEMD_SECTIONS.contains(&line.trim()))
+}
+
+/// Maps a systemd unit file onto `UnitFile`. `name` becomes the unit name, since systemd
+/// identifies units by file name rather than by a key inside the file. Every unit still needs
+/// an `ExecStart`, as `todo.path` is required regardless of unit type.
+pub(crate) fn parse_ini_unit(
+    contents: &str,
+    unit_type: UnitType,
+    name: &str,
+) -> Result<UnitFile, String> {
+    let mut description = None;
+    let mut provides = Vec::new();
+    let mut default_dependencies = true;
+    let mut dependency = DependencySection::default();
+    let mut condition = ConditionSection::default();
+    let mut install = InstallSection::default();
+    let mut todo = TodoSection::default();
+    let mut service = ServiceSection::default();
+    let mut timer = TimerSection::default();
+    let mut socket = SocketSection { service: default_socket_service(name), ..Default::default() };
+
+    for (line_no, section, key, value) in entries(contents)? {
+        let at = |msg: &str| format!("line {line_no}: {msg}");
+
+        match (section.as_str(), key.as_str()) {
+            ("Unit", "Description") => description = Some(value),
+            ("Unit", "DefaultDependencies") => {
+                default_dependencies = match value.to_ascii_lowercase().as_str() {
+                    "yes" | "true" | "on" | "1" => true,
+                    "no" | "false" | "off" | "0" => false,
+                    _ => return Err(at(&format!("invalid DefaultDependencies \"{value}\""))),
+                };
+            }
+            ("Unit", "Before") => dependency.needs_before.extend(words(&value)),
+            ("Unit", "After") => dependency.needs_after.extend(words(&value)),
+            ("Unit", "Requires") => dependency.requires.extend(words(&value)),
+            ("Unit", "Wants") => dependency.wants.extend(words(&value)),
+            ("Unit", "Conflicts") => dependency.conflicts.extend(words(&value)),
+            ("Unit", "ConditionPathExists") => match value.strip_prefix('!') {
+                Some(path) => condition.path_not_exists.push(path.to_string()),
+                None => condition.path_exists.push(value),
+            },
+            ("Service", "ExecStart") => {
+                let mut argv = split_command(strip_exec_prefix(&value)).map_err(|e| at(&e))?;
+                if argv.is_empty() {
+                    return Err(at("ExecStart is empty"));
+                }
+                todo.path = argv.remove(0);
+                todo.args = argv;
+            }
+            ("Service", "ExecStartPre") => todo.pre.push(exec_command(&value).map_err(|e| at(&e))?),
+            ("Service", "ExecStartPost") => {
+                todo.post.push(exec_command(&value).map_err(|e| at(&e))?);
+            }
+            ("Service", "Restart") => {
+                service.restart_policy = RestartPolicy::from_str(&value)
+                    .map_err(|_| at(&format!("invalid Restart policy \"{value}\"")))?;
+            }
+            ("Service", "ExecStop") => {
+                service.command_on_stop = Some(strip_exec_prefix(&value).to_string());
+            }
+            ("Service", "Environment") => {
+                for assignment in split_command(&value).map_err(|e| at(&e))? {
+                    let (k, v) = assignment.split_once('=').ok_or_else(|| {
+                        at(&format!("invalid Environment entry \"{assignment}\""))
+                    })?;
+                    todo.env.insert(k.to_string(), v.to_string());
+                }
+            }
+            ("Service", "EnvironmentFile") => todo.env_file.push(value),
+            ("Service", "WorkingDirectory") => service.working_directory = Some(value.into()),
+            ("Service", "User") => service.user = Some(value),
+            ("Service", "Group") => service.group = Some(value),
+            ("Service", "TimeoutStartSec") => {
+                service.start_timeout = Some(parse_duration(&value).map_err(|e| at(&e))?);
+            }
+            ("Service", "TimeoutStopSec") => {
+                service.stop_timeout = Some(parse_duration(&value).map_err(|e| at(&e))?);
+            }
+            ("Timer", "OnCalendar") => timer.on_calendar = Some(value),
+            ("Timer", "OnBootSec") => {
+                timer.on_boot_sec = Some(parse_duration(&value).map_err(|e| at(&e))?);
+            }
+            ("Timer", "OnUnitActiveSec") => {
+                timer.on_unit_active_sec = Some(parse_duration(&value).map_err(|e| at(&e))?);
+            }
+            ("Socket", "ListenStream") => socket.listen_stream.push(value),
+            ("Socket", "ListenDatagram") => socket.listen_datagram.push(value),
+            ("Socket", "Service") => socket.service = value,
+            ("Install", "Alias") => provides.extend(words(&value)),
+            ("Install", "WantedBy") => install.wanted_by.extend(words(&value)),
+            ("Install", "RequiredBy") => install.required_by.extend(words(&value)),
+            _ => {}
+        }
+    }
+
+    Ok(UnitFile {
+        unit: UnitSection {
+            unit_name: name.to_string(),
+            description,
+            unit_type,
+            provides,
+            default_dependencies,
+        },
+        todo,
+        service: (unit_type == UnitType::Service).then_some(service),
+        target: (unit_type == UnitType::Target)
+            .then_some(TargetSection { is_runnable_once: false }),
+        timer: (unit_type == UnitType::Timer).then_some(timer),
+        socket: (unit_type == UnitType::Socket).then_some(socket),
+        dependency,
+        condition,
+        install,
+        ..Default::default()
+    })
+}
+
+fn entries(contents: &str) -> Result<Vec<(usize, String, String, String)>, String> {
+    let mut entries = Vec::new();
+    let mut section = String::new();
+    let mut pending: Option<(usize, String)> = None;
+
+    for (i, raw) in contents.lines().enumerate() {
+        let line_no = i + 1;
+        let line = raw.trim();
+
+        let (start, line) = match pending.take() {
+            Some((start, mut joined)) => {
+                joined.push(' ');
+                joined.push_str(line);
+                (start, joined)
+            }
+            None if line.is_empty() || line.starts_with('#') || line.starts_with(';') => {
+                continue;
+            }
+            None => (line_no, line.to_string()),
+        };
+
+        if let Some(continued) = line.strip_suffix('\\') {
+            pending = Some((start, continued.trim_end().to_string()));
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {start}: expected Key=Value, found \"{line}\""))?;
+
+        if section.is_empty() {
+            return Err(format!("line {start}: entry outside of a section"));
+        }
+
+        entries.push((start, section.clone(), key.trim().to_string(), value.trim().to_string()));
+    }
+
+    if let Some((start, _)) = pending {
+        return Err(format!("line {start}: unterminated line continuation"));
+    }
+
+    Ok(entries)
+}
+
+/// systemd pairs `foo.socket` with `foo.service` unless `Service=` says otherwise.
+fn default_socket_service(name: &str) -> String {
+    let stem = name.strip_suffix(".socket").unwrap_or(name);
+    format!("{stem}.service")
+}
+
+fn words(value: &str) -> impl Iterator<Item = String> + '_ {
+    value.split_whitespace().map(str::to_string)
+}
+
+/// Drops systemd's `-`, `@`, `+`, `!` and `:` exec prefixes.
+fn exec_command(value: &str) -> Result<ExecCommand, String> {
+    let mut argv = split_command(strip_exec_prefix(value))?;
+    if argv.is_empty() {
+        return Err("command is empty".to_string());
+    }
+    let path = argv.remove(0);
+    Ok(ExecCommand { path, args: argv })
+}
+
+fn strip_exec_prefix(value: &str) -> &str {
+    value.trim_start_matches(['-', '@', '+', '!', ':'])
+}
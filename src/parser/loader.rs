@@ -1,66 +1,799 @@
-use std::{fs, path::Path, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
 
-use log::warn as logwarn;
-
-use crate::unit::errors::UnitLoadError;
+use crate::parser::observer::{LoadWarning, ObserverHandle};
+use crate::parser::{env_file, include, ini, span};
+use crate::unit::errors::{UnitLoadError, ValidationIssue};
+use crate::unit::merge::PartialUnitFile;
+use crate::unit::registry::UnitTypeRegistry;
 use crate::unit::types::{UnitFile, UnitType};
+use crate::unit::validator::{Validator, run_validators};
+
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    pub units: Vec<UnitFile>,
+    /// Template units such as `getty@`, which only run once instantiated.
+    pub templates: Vec<UnitFile>,
+    pub skipped: Vec<(PathBuf, UnitLoadError)>,
+}
+
+pub(crate) const INLINE_SOURCE: &str = "<string>";
+
+const JSON_EXTENSION: &str = "json";
+
+const DROP_IN_SUFFIX: &str = ".d";
+
+const DROP_IN_EXTENSION: &str = "conf";
+
+#[derive(Debug, Default, Clone)]
+pub struct LoadOptions {
+    pub registry: UnitTypeRegistry,
+
+    /// Fail with `UnknownField` on keys that do not map to any unit field, instead of silently
+    /// ignoring them. Applies to TOML and JSON units.
+    pub reject_unknown_fields: bool,
+
+    /// Fail with `NoUnitsFound` when loading a directory yields no units at all, whether it
+    /// is empty or every file in it was skipped.
+    pub require_units: bool,
+
+    /// When set, directory loads only read files whose extension maps to one of these types.
+    /// Other unit files are passed over without being read or reported as skipped.
+    pub unit_types: Option<Vec<UnitType>>,
+
+    /// Run `UnitFile::resolve_paths` against each unit's directory after loading it.
+    pub resolve_paths: bool,
+
+    pub observer: ObserverHandle,
+}
 
-pub fn load_unit(path: &str) -> Result<UnitFile, UnitLoadError> {
-    let path = Path::new(path);
-    let ext_str =
-        path.extension().and_then(|s| s.to_str()).ok_or(UnitLoadError::InvalidExtension)?;
+/// How a unit file is written. `detect_type` picks TOML or JSON from the extension, and
+/// parsing switches TOML to INI when the contents look like a systemd unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitFormat {
+    Toml,
+    Json,
+    Ini,
+}
 
-    let ext_type = UnitType::from_str(ext_str)
-        .map_err(|_| UnitLoadError::UnsupportedUnitType(ext_str.to_string()))?;
+pub fn load_unit(path: impl AsRef<Path>) -> Result<UnitFile, UnitLoadError> {
+    load_unit_with_options(path, &LoadOptions::default())
+}
 
-    let contents =
-        fs::read_to_string(path).map_err(|e| UnitLoadError::ReadError(path.to_path_buf(), e))?;
+pub fn load_unit_with_options(
+    path: impl AsRef<Path>,
+    options: &LoadOptions,
+) -> Result<UnitFile, UnitLoadError> {
+    read_unit(path.as_ref(), options, &[])
+}
 
-    let unit: UnitFile =
-        toml::from_str(&contents).map_err(|e| UnitLoadError::ParseError(path.to_path_buf(), e))?;
+pub fn load_unit_with_registry(
+    path: impl AsRef<Path>,
+    registry: &UnitTypeRegistry,
+) -> Result<UnitFile, UnitLoadError> {
+    let options = LoadOptions { registry: registry.clone(), ..Default::default() };
+    load_unit_with_options(path, &options)
+}
+
+/// Loads a unit with its type taken from `extensions` instead of the built-in extensions.
+pub fn load_unit_with_extensions(
+    path: impl AsRef<Path>,
+    extensions: &HashMap<String, UnitType>,
+) -> Result<UnitFile, UnitLoadError> {
+    load_unit_with_registry(path, &UnitTypeRegistry::from(extensions.clone()))
+}
+
+/// Loads a unit and runs `validators` after the built-in checks, reporting the issues from
+/// all of them in a single `ValidationError`.
+pub fn load_unit_with_validators(
+    path: impl AsRef<Path>,
+    validators: &[&dyn Validator],
+) -> Result<UnitFile, UnitLoadError> {
+    read_unit(path.as_ref(), &LoadOptions::default(), validators)
+}
+
+fn read_unit(
+    path: &Path,
+    options: &LoadOptions,
+    validators: &[&dyn Validator],
+) -> Result<UnitFile, UnitLoadError> {
+    let kind = detect_type(path, &options.registry)?;
+    let contents = read_unit_file(path)?;
+    parse_unit_contents(path, &contents, kind, options, validators, &Disk)
+}
+
+/// The second step of `load_unit`: reads the file, failing only with `ReadError`.
+pub fn read_unit_file(path: impl AsRef<Path>) -> Result<String, UnitLoadError> {
+    let path = path.as_ref();
+    fs::read_to_string(path).map_err(|e| UnitLoadError::ReadError(path.to_path_buf(), e))
+}
 
-    if unit.validate().is_err() || unit.unit.unit_type != ext_type {
-        return Err(UnitLoadError::ValidationError(path.to_path_buf()));
+/// The last step of `load_unit`: parses the `contents` of the unit file at `path` and
+/// validates it. `path` is the unit's origin; includes and env files are resolved against
+/// it. Together with `detect_type` and `read_unit_file` this is exactly
+/// `load_unit_with_options`.
+pub fn parse_unit_file(
+    path: impl AsRef<Path>,
+    contents: &str,
+    kind: (UnitType, UnitFormat),
+    options: &LoadOptions,
+) -> Result<UnitFile, UnitLoadError> {
+    parse_unit_contents(path.as_ref(), contents, kind, options, &[], &Disk)
+}
+
+pub(crate) fn parse_unit_contents(
+    path: &Path,
+    contents: &str,
+    (ext_type, mut format): (UnitType, UnitFormat),
+    options: &LoadOptions,
+    validators: &[&dyn Validator],
+    files: &dyn Files,
+) -> Result<UnitFile, UnitLoadError> {
+    if format == UnitFormat::Toml && ini::is_systemd_unit(contents) {
+        format = UnitFormat::Ini;
     }
 
+    let resolved = match format {
+        UnitFormat::Toml => include::resolve_includes(path, contents, files)?,
+        _ => None,
+    };
+    let source =
+        Source { path, format, options, files, unmerged: resolved.is_some().then_some(contents) };
+    let contents = resolved.as_deref().unwrap_or(contents);
+    let mut unit = parse_unit_from(contents, ext_type, &source, validators)?;
+
+    if options.resolve_paths {
+        unit.resolve_paths(path.parent().unwrap_or(Path::new("")));
+    }
+    unit.origin = Some(path.to_path_buf());
     Ok(unit)
 }
 
-pub fn load_units(dir_path: &str) -> Result<Vec<UnitFile>, UnitLoadError> {
-    let dir = Path::new(dir_path);
-    let entries =
-        fs::read_dir(dir).map_err(|e| UnitLoadError::ReadDirError(dir.to_path_buf(), e))?;
+/// Parses TOML `contents` as a unit of `unit_type` and validates it. Errors name `<string>`
+/// as their path, and relative env files are resolved against the working directory. Use
+/// `parse_unit_file` for contents read from a unit file.
+pub fn parse_unit(contents: &str, unit_type: UnitType) -> Result<UnitFile, UnitLoadError> {
+    let options = LoadOptions::default();
+    let source = Source {
+        path: Path::new(INLINE_SOURCE),
+        format: UnitFormat::Toml,
+        options: &options,
+        files: &Disk,
+        unmerged: None,
+    };
+    parse_unit_from(contents, unit_type, &source, &[])
+}
+
+/// The first step of `load_unit`: splits `name.service` into (`Service`, TOML) and
+/// `name.service.json` into (`Service`, JSON), with types looked up in `registry`. The file
+/// is not read, so this fails only with `InvalidExtension` or `UnsupportedUnitType`.
+pub fn detect_type(
+    path: impl AsRef<Path>,
+    registry: &UnitTypeRegistry,
+) -> Result<(UnitType, UnitFormat), UnitLoadError> {
+    let path = path.as_ref();
+    fn ext_of(path: &Path) -> Result<&str, UnitLoadError> {
+        path.extension().and_then(|s| s.to_str()).ok_or(UnitLoadError::InvalidExtension)
+    }
+
+    let mut ext_str = ext_of(path)?;
+    let mut format = UnitFormat::Toml;
+
+    if ext_str == JSON_EXTENSION {
+        ext_str = ext_of(Path::new(path.file_stem().unwrap_or_default()))?;
+        format = UnitFormat::Json;
+    }
+
+    let ext_type = registry
+        .resolve(ext_str)
+        .ok_or_else(|| UnitLoadError::UnsupportedUnitType(ext_str.to_string()))?;
+
+    Ok((ext_type, format))
+}
+
+/// Where unit files and the files they refer to, their includes and env files, are read from.
+pub(crate) trait Files {
+    fn read(&self, path: &Path) -> io::Result<String>;
+
+    /// The key `path` is tracked under when detecting include cycles.
+    fn canonical(&self, path: &Path) -> PathBuf;
+
+    /// The entries of directory `dir`, in no particular order.
+    fn list(&self, dir: &Path) -> io::Result<Vec<io::Result<PathBuf>>>;
+}
+
+/// The host filesystem, used for everything but archives.
+pub(crate) struct Disk;
+
+impl Files for Disk {
+    fn read(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn canonical(&self, path: &Path) -> PathBuf {
+        fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<io::Result<PathBuf>>> {
+        Ok(fs::read_dir(dir)?.map(|entry| entry.map(|entry| entry.path())).collect())
+    }
+}
 
+struct Source<'a> {
+    path: &'a Path,
+    format: UnitFormat,
+    options: &'a LoadOptions,
+    files: &'a dyn Files,
+    /// The file as written when the contents being parsed are its includes merged with it.
+    unmerged: Option<&'a str>,
+}
+
+fn parse_unit_from(
+    contents: &str,
+    unit_type: UnitType,
+    source: &Source,
+    validators: &[&dyn Validator],
+) -> Result<UnitFile, UnitLoadError> {
+    let Source { path: source, format, options, files, unmerged } = *source;
+    let validation_error = |issues: Vec<ValidationIssue>| {
+        let issues = match (format, unmerged) {
+            (UnitFormat::Toml, None) => span::locate(contents, issues),
+            // Keys missing from the file itself came from an include, so only exact matches
+            // are located.
+            (UnitFormat::Toml, Some(written)) => span::locate_exact(written, issues),
+            (UnitFormat::Json | UnitFormat::Ini, _) => span::unlocated(issues),
+        };
+        UnitLoadError::ValidationError(source.to_path_buf(), issues)
+    };
+    let mut unknown = None;
+    let mut on_unknown = |field: serde_ignored::Path| {
+        if options.reject_unknown_fields && unknown.is_none() {
+            unknown = Some(field.to_string());
+        }
+    };
+
+    let mut unit: UnitFile = match format {
+        UnitFormat::Toml => {
+            let deserializer = toml::Deserializer::parse(contents)
+                .map_err(|e| UnitLoadError::ParseError(source.to_path_buf(), e))?;
+            serde_ignored::deserialize(deserializer, &mut on_unknown)
+                .map_err(|e| UnitLoadError::ParseError(source.to_path_buf(), e))?
+        }
+        UnitFormat::Json => {
+            let mut deserializer = serde_json::Deserializer::from_str(contents);
+            serde_ignored::deserialize(&mut deserializer, &mut on_unknown)
+                .map_err(|e| UnitLoadError::JsonParseError(source.to_path_buf(), e))?
+        }
+        UnitFormat::Ini => {
+            let name = source.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+            ini::parse_ini_unit(contents, unit_type, name)
+                .map_err(|e| UnitLoadError::IniParseError(source.to_path_buf(), e))?
+        }
+    };
+
+    if let Some(field) = unknown {
+        return Err(UnitLoadError::UnknownField(source.to_path_buf(), field));
+    }
+
+    for value in unit.trim_names() {
+        options.observer.warn(LoadWarning::TrimmedName { path: source.to_path_buf(), value });
+    }
+
+    unit.todo.apply_command().map_err(|issue| validation_error(vec![issue]))?;
+
+    env_file::apply_env_files(&mut unit.todo, source, files)?;
+
+    run_validators(&unit, validators).map_err(validation_error)?;
+
+    if unit.unit.unit_type != unit_type {
+        return Err(UnitLoadError::TypeMismatch {
+            path: source.to_path_buf(),
+            expected: unit_type,
+            found: unit.unit.unit_type,
+        });
+    }
+
+    Ok(unit)
+}
+
+pub fn load_units(dir: impl AsRef<Path>) -> Result<Vec<UnitFile>, UnitLoadError> {
+    load_units_report(dir).map(|report| report.units)
+}
+
+pub fn load_units_report(dir: impl AsRef<Path>) -> Result<LoadReport, UnitLoadError> {
+    load_units_with_registry(dir, &UnitTypeRegistry::default())
+}
+
+/// Like `load_units`, but stops at the first unit file that fails to load and returns it with
+/// its path instead of skipping it. Files whose extension is not a unit type, such as a
+/// `README`, are still ignored.
+pub fn load_units_strict(
+    dir: impl AsRef<Path>,
+) -> Result<Vec<UnitFile>, Box<(PathBuf, UnitLoadError)>> {
+    load_units_strict_with_options(dir, &LoadOptions::default())
+}
+
+pub fn load_units_strict_with_options(
+    dir: impl AsRef<Path>,
+    options: &LoadOptions,
+) -> Result<Vec<UnitFile>, Box<(PathBuf, UnitLoadError)>> {
+    let dir = dir.as_ref();
     let mut units = Vec::new();
+    for unit in load_units_iter_with_options(dir, options) {
+        match unit {
+            Ok(unit) if !unit.is_template() => units.push(unit),
+            Ok(_) => {}
+            Err(err)
+                if matches!(
+                    err.1,
+                    UnitLoadError::InvalidExtension | UnitLoadError::UnsupportedUnitType(_)
+                ) => {}
+            Err(err) => return Err(err),
+        }
+    }
+    if options.require_units && units.is_empty() {
+        return Err(Box::new((dir.to_path_buf(), UnitLoadError::NoUnitsFound(dir.to_path_buf()))));
+    }
+    Ok(units)
+}
+
+/// Loads the files in `dir` one at a time, in the same order and with the same duplicate
+/// checks as `load_units`. Templates are yielded too; filter on `UnitFile::is_template`.
+/// Failures are only reported through the `Err` items, including a single one for `dir` itself
+/// if it cannot be read. Warnings about units that did load, such as `TrimmedName`, still go to
+/// the default observer and so to `log::warn`.
+pub fn load_units_iter(
+    dir: impl AsRef<Path>,
+) -> impl Iterator<Item = Result<UnitFile, Box<(PathBuf, UnitLoadError)>>> {
+    load_units_iter_with_options(dir, &LoadOptions::default())
+}
+
+/// Like `load_units_iter`, but loads under `options` and sends warnings to its observer, so
+/// pass a silent one to log nothing. Files `LoadOptions::unit_types` leaves out are not yielded
+/// at all.
+pub fn load_units_iter_with_options<P: AsRef<Path>>(
+    dir: P,
+    options: &LoadOptions,
+) -> impl Iterator<Item = Result<UnitFile, Box<(PathBuf, UnitLoadError)>>> + use<P> {
+    let dir = dir.as_ref().to_path_buf();
+    let paths: Vec<Result<PathBuf, UnitLoadError>> = match list_dir(&dir, &Disk) {
+        Ok((paths, errors)) => {
+            let paths = paths.into_iter().filter(|path| wanted(path, options));
+            errors.into_iter().map(Err).chain(paths.map(Ok)).collect()
+        }
+        Err(err) => vec![Err(err)],
+    };
+
+    let options = options.clone();
+    let mut seen: HashMap<String, PathBuf> = HashMap::new();
+    paths.into_iter().map(move |path| {
+        let path = path.map_err(|err| Box::new((dir.clone(), err)))?;
+        read_unit(&path, &options, &[])
+            .and_then(|unit| claim(&mut seen, &unit, &path).map(|()| unit))
+            .map_err(|err| Box::new((path, err)))
+    })
+}
+
+/// Loads the units in `dir` whose type is one of `types`.
+pub fn load_units_filtered(
+    dir: impl AsRef<Path>,
+    types: &[UnitType],
+) -> Result<Vec<UnitFile>, UnitLoadError> {
+    let options = LoadOptions { unit_types: Some(types.to_vec()), ..Default::default() };
+    load_units_with_options(dir, &options).map(|report| report.units)
+}
+
+pub fn load_units_with_registry(
+    dir: impl AsRef<Path>,
+    registry: &UnitTypeRegistry,
+) -> Result<LoadReport, UnitLoadError> {
+    let options = LoadOptions { registry: registry.clone(), ..Default::default() };
+    load_units_with_options(dir, &options)
+}
+
+pub fn load_units_with_options(
+    dir: impl AsRef<Path>,
+    options: &LoadOptions,
+) -> Result<LoadReport, UnitLoadError> {
+    let dir = dir.as_ref();
+    let mut scanner = DirScanner::new(None, options);
+    scanner.scan(dir, 0)?;
+    scanner.finish(dir)
+}
+
+/// Loads units from `dir` and its subdirectories, descending at most `max_depth` levels.
+/// Directories reached twice (e.g. through a symlink loop) are only scanned once.
+pub fn load_units_recursive(
+    dir: impl AsRef<Path>,
+    max_depth: usize,
+) -> Result<Vec<UnitFile>, UnitLoadError> {
+    load_units_recursive_with_options(dir, max_depth, &LoadOptions::default())
+}
+
+pub fn load_units_recursive_with_options(
+    dir: impl AsRef<Path>,
+    max_depth: usize,
+    options: &LoadOptions,
+) -> Result<Vec<UnitFile>, UnitLoadError> {
+    let dir = dir.as_ref();
+    let mut scanner = DirScanner::new(Some(max_depth), options);
+    if let Ok(canonical) = fs::canonicalize(dir) {
+        scanner.visited.insert(canonical);
+    }
+    scanner.scan(dir, 0)?;
+    scanner.finish(dir).map(|report| report.units)
+}
+
+/// Loads the units in `base_dir` that are enabled, i.e. that a symlink directly inside
+/// `enabled_dir` points to, as with systemd's `*.wants/` directories. The link's own name does
+/// not matter, only the file it resolves to. Entries of `enabled_dir` that are not symlinks,
+/// or whose target is missing, are ignored with a warning.
+pub fn load_enabled_units(
+    base_dir: impl AsRef<Path>,
+    enabled_dir: impl AsRef<Path>,
+) -> Result<Vec<UnitFile>, UnitLoadError> {
+    load_enabled_units_with_options(base_dir, enabled_dir, &LoadOptions::default())
+}
 
-    for entry_result in entries {
-        let entry = match entry_result {
-            Ok(e) => e,
+pub fn load_enabled_units_with_options(
+    base_dir: impl AsRef<Path>,
+    enabled_dir: impl AsRef<Path>,
+    options: &LoadOptions,
+) -> Result<Vec<UnitFile>, UnitLoadError> {
+    let observer = &options.observer;
+    let mut enabled = HashSet::new();
+    for (_, link) in sorted_entries(enabled_dir.as_ref())? {
+        if !link.is_symlink() {
+            let reason = "not a symlink".to_string();
+            observer.warn(LoadWarning::IgnoredEnabledLink { path: link, reason });
+            continue;
+        }
+        match fs::canonicalize(&link) {
+            Ok(target) => {
+                enabled.insert(target);
+            }
             Err(e) => {
-                return Err(UnitLoadError::DirEntryError(dir.to_path_buf(), e));
+                let reason = format!("cannot resolve link target: {e}");
+                observer.warn(LoadWarning::IgnoredEnabledLink { path: link, reason });
             }
-        };
+        }
+    }
+
+    let units = load_units_with_options(base_dir, options)?.units;
+    Ok(units
+        .into_iter()
+        .filter(|unit| {
+            unit.origin()
+                .and_then(|origin| fs::canonicalize(origin).ok())
+                .is_some_and(|origin| enabled.contains(&origin))
+        })
+        .collect())
+}
+
+/// Loads every file matching `pattern`, e.g. `/etc/neptune/**/*.service`. Matches whose
+/// extension is not a known unit type are ignored; units that fail to load are skipped with a
+/// warning, as in `load_units`.
+pub fn load_units_glob(pattern: &str) -> Result<Vec<UnitFile>, UnitLoadError> {
+    load_units_glob_with_options(pattern, &LoadOptions::default())
+}
+
+pub fn load_units_glob_with_options(
+    pattern: &str,
+    options: &LoadOptions,
+) -> Result<Vec<UnitFile>, UnitLoadError> {
+    let paths = glob::glob(pattern)
+        .map_err(|e| UnitLoadError::InvalidGlobPattern(pattern.to_string(), e))?;
+
+    let mut scanner = DirScanner::new(None, options);
+    for entry in paths {
+        match entry {
+            Ok(path) => {
+                if path.is_file() && detect_type(&path, &options.registry).is_ok() {
+                    scanner.load(path);
+                }
+            }
+            Err(e) => {
+                let path = e.path().to_path_buf();
+                scanner.skip(path.clone(), UnitLoadError::ReadError(path, e.into()));
+            }
+        }
+    }
 
-        let pathbuf = entry.path();
-        let path = match pathbuf.to_str() {
-            Some(p) => p,
-            None => {
-                logwarn!("Skipping entry with invalid UTF-8 path: {:?}", pathbuf);
+    scanner.finish(Path::new(pattern)).map(|report| report.units)
+}
+
+/// Loads the units in `base_dir`, then applies drop-in fragments from each of `override_dirs`
+/// in order. Fragments for `foo.service` are the `*.conf` files in `<dir>/foo.service.d/`,
+/// written as TOML [`PartialUnitFile`]s and applied in file name order via
+/// [`UnitFile::merge`]. Overridden units are validated again once all fragments are applied.
+pub fn load_units_with_overrides(
+    base_dir: impl AsRef<Path>,
+    override_dirs: &[&Path],
+) -> Result<Vec<UnitFile>, UnitLoadError> {
+    load_units_with_overrides_and_options(base_dir, override_dirs, &LoadOptions::default())
+}
+
+pub fn load_units_with_overrides_and_options(
+    base_dir: impl AsRef<Path>,
+    override_dirs: &[&Path],
+    options: &LoadOptions,
+) -> Result<Vec<UnitFile>, UnitLoadError> {
+    let mut units = load_units_with_options(base_dir, options)?.units;
+    let observer = &options.observer;
+
+    for dir in override_dirs {
+        for (name, drop_in) in sorted_entries(dir)? {
+            let Some(name) = name.strip_suffix(DROP_IN_SUFFIX) else {
+                continue;
+            };
+            if !drop_in.is_dir() {
                 continue;
             }
-        };
 
-        let display_path = pathbuf.display();
+            let Some(unit) = units.iter_mut().find(|unit| unit.unit.unit_name == name) else {
+                let unit = name.to_string();
+                observer.warn(LoadWarning::IgnoredOverride { path: drop_in, unit });
+                continue;
+            };
+
+            for (_, fragment) in sorted_entries(&drop_in)? {
+                if fragment.extension().and_then(|s| s.to_str()) != Some(DROP_IN_EXTENSION) {
+                    continue;
+                }
+                let contents = fs::read_to_string(&fragment)
+                    .map_err(|e| UnitLoadError::ReadError(fragment.clone(), e))?;
+                let partial: PartialUnitFile = toml::from_str(&contents)
+                    .map_err(|e| UnitLoadError::ParseError(fragment.clone(), e))?;
+                unit.merge(&partial);
+            }
+
+            unit.validate().map_err(|errors| {
+                UnitLoadError::ValidationError(drop_in, span::unlocated(errors))
+            })?;
+        }
+    }
+
+    Ok(units)
+}
+
+pub(crate) fn sorted_entries(dir: &Path) -> Result<Vec<(String, PathBuf)>, UnitLoadError> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| UnitLoadError::ReadDirError(dir.to_path_buf(), e))? {
+        let entry = entry.map_err(|e| UnitLoadError::DirEntryError(dir.to_path_buf(), e))?;
+        entries.push((entry.file_name().to_string_lossy().into_owned(), entry.path()));
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+/// The paths in `dir` in sorted order, plus an error for each entry that could not be read.
+fn list_dir(
+    dir: &Path,
+    files: &dyn Files,
+) -> Result<(Vec<PathBuf>, Vec<UnitLoadError>), UnitLoadError> {
+    let entries = files.list(dir).map_err(|e| UnitLoadError::ReadDirError(dir.to_path_buf(), e))?;
+
+    let (mut paths, mut errors) = (Vec::new(), Vec::new());
+    for entry in entries {
+        match entry {
+            Ok(path) => paths.push(path),
+            Err(e) => errors.push(UnitLoadError::DirEntryError(dir.to_path_buf(), e)),
+        }
+    }
+    paths.sort();
+    Ok((paths, errors))
+}
+
+/// Whether `path` passes `LoadOptions::unit_types`. Files whose type cannot be told from the
+/// extension are let through, so that loading them reports why.
+fn wanted(path: &Path, options: &LoadOptions) -> bool {
+    match (&options.unit_types, detect_type(path, &options.registry)) {
+        (Some(types), Ok((unit_type, _))) => types.contains(&unit_type),
+        _ => true,
+    }
+}
+
+/// Records that `unit` was loaded from `path`, or fails with `DuplicateUnitName` if an earlier
+/// file already defined a unit of that name.
+fn claim(
+    seen: &mut HashMap<String, PathBuf>,
+    unit: &UnitFile,
+    path: &Path,
+) -> Result<(), UnitLoadError> {
+    if let Some(first) = seen.get(&unit.unit.unit_name) {
+        return Err(UnitLoadError::DuplicateUnitName(
+            unit.unit.unit_name.clone(),
+            first.clone(),
+            path.to_path_buf(),
+        ));
+    }
+    seen.insert(unit.unit.unit_name.clone(), path.to_path_buf());
+    Ok(())
+}
+
+pub(crate) struct DirScanner<'a> {
+    pub(crate) report: LoadReport,
+    seen: HashMap<String, PathBuf>,
+    visited: HashSet<PathBuf>,
+    max_depth: Option<usize>,
+    options: &'a LoadOptions,
+    files: &'a dyn Files,
+}
+
+impl<'a> DirScanner<'a> {
+    pub(crate) fn new(max_depth: Option<usize>, options: &'a LoadOptions) -> Self {
+        Self::with_files(max_depth, options, &Disk)
+    }
 
-        match load_unit(path) {
+    /// Reads directories, unit files and what they refer to from `files` instead of the disk.
+    pub(crate) fn with_files(
+        max_depth: Option<usize>,
+        options: &'a LoadOptions,
+        files: &'a dyn Files,
+    ) -> Self {
+        Self {
+            report: LoadReport::default(),
+            seen: HashMap::new(),
+            visited: HashSet::new(),
+            max_depth,
+            options,
+            files,
+        }
+    }
+
+    fn scan(&mut self, dir: &Path, depth: usize) -> Result<(), UnitLoadError> {
+        let (paths, errors) = list_dir(dir, self.files)?;
+        for err in errors {
+            self.skip(dir.to_path_buf(), err);
+        }
+
+        for pathbuf in paths {
+            if let Some(max_depth) = self.max_depth
+                && pathbuf.is_dir()
+            {
+                self.descend(pathbuf, depth + 1, max_depth);
+                continue;
+            }
+
+            self.load(pathbuf);
+        }
+
+        Ok(())
+    }
+
+    fn descend(&mut self, dir: PathBuf, depth: usize, max_depth: usize) {
+        if depth > max_depth {
+            let reason = format!("maximum depth {max_depth} reached");
+            self.options.observer.warn(LoadWarning::SkippedDirectory { path: dir, reason });
+            return;
+        }
+
+        match fs::canonicalize(&dir) {
+            Ok(canonical) => {
+                if !self.visited.insert(canonical) {
+                    let reason = "directory already visited".to_string();
+                    self.options.observer.warn(LoadWarning::SkippedDirectory { path: dir, reason });
+                    return;
+                }
+            }
+            Err(e) => {
+                self.skip(dir.clone(), UnitLoadError::ReadDirError(dir, e));
+                return;
+            }
+        }
+
+        if let Err(err) = self.scan(&dir, depth) {
+            self.skip(dir, err);
+        }
+    }
+
+    pub(crate) fn load(&mut self, pathbuf: PathBuf) {
+        if !self.wants(&pathbuf) {
+            return;
+        }
+
+        let result = self.read(&pathbuf);
+        self.accept(pathbuf, result);
+    }
+
+    /// Whether `path` passes `LoadOptions::unit_types`; see `wanted`.
+    pub(crate) fn wants(&self, path: &Path) -> bool {
+        wanted(path, self.options)
+    }
+
+    /// The report of everything loaded so far, or `NoUnitsFound` naming `source` if it holds no
+    /// units and `LoadOptions::require_units` is set.
+    pub(crate) fn finish(self, source: &Path) -> Result<LoadReport, UnitLoadError> {
+        if self.options.require_units && self.report.units.is_empty() {
+            return Err(UnitLoadError::NoUnitsFound(source.to_path_buf()));
+        }
+        Ok(self.report)
+    }
+
+    /// Loads the unit at `path` as `load_unit_with_options` does, but through `files`.
+    pub(crate) fn read(&self, path: &Path) -> Result<UnitFile, UnitLoadError> {
+        let kind = detect_type(path, &self.options.registry)?;
+        let contents =
+            self.files.read(path).map_err(|e| UnitLoadError::ReadError(path.to_path_buf(), e))?;
+        parse_unit_contents(path, &contents, kind, self.options, &[], self.files)
+    }
+
+    /// Adds a loaded unit to the report, or records why it was skipped. Returns whether the
+    /// unit was kept.
+    pub(crate) fn accept(
+        &mut self,
+        pathbuf: PathBuf,
+        result: Result<UnitFile, UnitLoadError>,
+    ) -> bool {
+        match result {
             Ok(unit) => {
-                units.push(unit);
+                if let Err(err) = claim(&mut self.seen, &unit, &pathbuf) {
+                    self.skip(pathbuf, err);
+                    return false;
+                }
+                if unit.is_template() {
+                    self.report.templates.push(unit);
+                } else {
+                    self.report.units.push(unit);
+                }
+                true
             }
             Err(err) => {
-                logwarn!("Skipping {} due to error: {}", display_path, err);
+                self.skip(pathbuf, err);
+                false
             }
         }
     }
 
-    Ok(units)
+    pub(crate) fn skip(&mut self, path: PathBuf, err: UnitLoadError) {
+        let reason = err.to_string();
+        self.options.observer.warn(LoadWarning::SkippedFile { path: path.clone(), reason });
+        self.report.skipped.push((path, err));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOOD: &str = "[unit]\nname = \"good\"\ntype = \"service\"\n\n\
+                        [todo]\npath = \"/usr/bin/good\"\n\n[service]\n";
+
+    /// A directory holding `good.service` whose listing also yields an unreadable entry.
+    struct BrokenListing;
+
+    impl Files for BrokenListing {
+        fn read(&self, path: &Path) -> io::Result<String> {
+            match path.file_name().and_then(|name| name.to_str()) {
+                Some("good.service") => Ok(GOOD.to_string()),
+                _ => Err(io::ErrorKind::NotFound.into()),
+            }
+        }
+
+        fn canonical(&self, path: &Path) -> PathBuf {
+            path.to_path_buf()
+        }
+
+        fn list(&self, dir: &Path) -> io::Result<Vec<io::Result<PathBuf>>> {
+            Ok(vec![Err(io::Error::other("bad entry")), Ok(dir.join("good.service"))])
+        }
+    }
+
+    #[test]
+    fn entry_error_is_recorded_and_loading_continues() {
+        let options = LoadOptions::default();
+        let mut scanner = DirScanner::with_files(None, &options, &BrokenListing);
+        scanner.scan(Path::new("/units"), 0).unwrap();
+
+        let report = scanner.report;
+        assert_eq!(report.units.len(), 1);
+        assert_eq!(report.units[0].unit.unit_name, "good");
+        assert!(matches!(
+            report.skipped.as_slice(),
+            [(path, UnitLoadError::DirEntryError(..))] if path == Path::new("/units")
+        ));
+    }
 }
@@ -0,0 +1,135 @@
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use log::warn as logwarn;
+
+use crate::unit::types::UnitType;
+
+/// Something the loader or generator recovered from instead of failing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadWarning {
+    SkippedFile { path: PathBuf, reason: String },
+    SkippedDirectory { path: PathBuf, reason: String },
+    IgnoredOverride { path: PathBuf, unit: String },
+    IgnoredEnabledLink { path: PathBuf, reason: String },
+    MissingDependency { unit: String, dependency: String },
+    MissingWant { unit: String, dependency: String },
+    TrimmedName { path: PathBuf, value: String },
+    DuplicateDependency { unit: String, dependency: String },
+    MissingInstallTarget { unit: String, target: String },
+    CrossTypeOrdering { before: String, before_type: UnitType, after: String, after_type: UnitType },
+    UnmatchedPattern { unit: String, pattern: String },
+}
+
+impl fmt::Display for LoadWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadWarning::SkippedFile { path, reason } => {
+                write!(f, "Skipping {} due to error: {}", path.display(), reason)
+            }
+            LoadWarning::SkippedDirectory { path, reason } => {
+                write!(f, "Not descending into {}: {}", path.display(), reason)
+            }
+            LoadWarning::IgnoredOverride { path, unit } => {
+                write!(f, "Ignoring overrides in {}: no unit named {}", path.display(), unit)
+            }
+            LoadWarning::IgnoredEnabledLink { path, reason } => {
+                write!(f, "Ignoring {}: {}", path.display(), reason)
+            }
+            LoadWarning::MissingDependency { unit, dependency } => {
+                write!(f, "\"{unit}\" depends on missing unit \"{dependency}\", ignoring")
+            }
+            LoadWarning::MissingWant { unit, dependency } => {
+                write!(f, "\"{unit}\" wants missing unit \"{dependency}\", ignoring")
+            }
+            LoadWarning::CrossTypeOrdering { before, before_type, after, after_type } => {
+                write!(f, "{before_type} \"{before}\" is ordered before {after_type} \"{after}\"")
+            }
+            LoadWarning::MissingInstallTarget { unit, target } => {
+                write!(f, "\"{unit}\" is wanted by missing unit \"{target}\", ignoring")
+            }
+            LoadWarning::UnmatchedPattern { unit, pattern } => {
+                write!(f, "\"{unit}\" lists pattern \"{pattern}\" that matches no unit, ignoring")
+            }
+            LoadWarning::DuplicateDependency { unit, dependency } => {
+                write!(f, "\"{unit}\" lists \"{dependency}\" more than once")
+            }
+            LoadWarning::TrimmedName { path, value } => {
+                write!(f, "Trimmed surrounding whitespace from \"{}\" in {}", value, path.display())
+            }
+        }
+    }
+}
+
+pub trait Observer: Send + Sync {
+    fn on_warning(&self, warning: LoadWarning);
+}
+
+/// Forwards warnings to `log::warn!`. This is what the loader uses unless told otherwise.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogObserver;
+
+impl Observer for LogObserver {
+    fn on_warning(&self, warning: LoadWarning) {
+        logwarn!("{}", warning);
+    }
+}
+
+/// Keeps every warning it receives.
+#[derive(Debug, Default)]
+pub struct CollectingObserver {
+    warnings: Mutex<Vec<LoadWarning>>,
+}
+
+impl CollectingObserver {
+    pub fn warnings(&self) -> Vec<LoadWarning> {
+        self.warnings.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn take(&self) -> Vec<LoadWarning> {
+        std::mem::take(&mut *self.warnings.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Observer for CollectingObserver {
+    fn on_warning(&self, warning: LoadWarning) {
+        self.warnings.lock().unwrap_or_else(|e| e.into_inner()).push(warning);
+    }
+}
+
+/// Shared handle to an [`Observer`], stored in `LoadOptions` and `GenerateOptions`.
+/// Defaults to [`LogObserver`]. Build one from an `Arc` to keep access to the observer, e.g.
+/// to read a [`CollectingObserver`] afterwards.
+#[derive(Clone)]
+pub struct ObserverHandle(Arc<dyn Observer>);
+
+impl ObserverHandle {
+    pub fn new(observer: impl Observer + 'static) -> Self {
+        Self(Arc::new(observer))
+    }
+
+    pub(crate) fn warn(&self, warning: LoadWarning) {
+        self.0.on_warning(warning);
+    }
+}
+
+impl Default for ObserverHandle {
+    fn default() -> Self {
+        Self::new(LogObserver)
+    }
+}
+
+impl fmt::Debug for ObserverHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ObserverHandle")
+    }
+}
+
+impl<O: Observer + 'static> From<Arc<O>> for ObserverHandle {
+    fn from(observer: Arc<O>) -> Self {
+        Self(observer)
+    }
+}
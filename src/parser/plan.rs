@@ -0,0 +1,55 @@
+use petgraph::Direction;
+use serde::Serialize;
+
+use crate::parser::generator::{GenerateOptions, plan};
+use crate::unit::errors::GraphBuildError;
+use crate::unit::types::{UnitFile, UnitType};
+
+/// Bumped whenever the shape of `generate_plan_json` output changes.
+pub const PLAN_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Plan<'a> {
+    schema_version: u32,
+    units: Vec<PlannedUnit<'a>>,
+}
+
+#[derive(Serialize)]
+struct PlannedUnit<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    unit_type: UnitType,
+    order_index: usize,
+    depends_on: Vec<&'a str>,
+}
+
+/// Serializes the `generate` order as `{ "schema_version": 1, "units": [...] }`, where each
+/// entry has the unit's `name`, `type`, `order_index` and `depends_on`: the sorted names of
+/// the units that must start before it, with aliases resolved.
+pub fn generate_plan_json(units: &[UnitFile]) -> Result<String, GraphBuildError> {
+    let (unit_graph, sorted) = plan(units, &GenerateOptions::default())?;
+
+    let units = sorted
+        .into_iter()
+        .enumerate()
+        .map(|(order_index, node)| {
+            let unit = &units[node.index()];
+            let mut depends_on: Vec<&str> = unit_graph
+                .inner()
+                .neighbors_directed(node, Direction::Incoming)
+                .map(|dep| units[dep.index()].unit.unit_name.as_str())
+                .collect();
+            depends_on.sort_unstable();
+            depends_on.dedup();
+            PlannedUnit {
+                name: &unit.unit.unit_name,
+                unit_type: unit.unit.unit_type,
+                order_index,
+                depends_on,
+            }
+        })
+        .collect();
+
+    let plan = Plan { schema_version: PLAN_SCHEMA_VERSION, units };
+    Ok(serde_json::to_string_pretty(&plan)?)
+}
@@ -0,0 +1,161 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use strum_macros::Display;
+
+use crate::parser::loader::INLINE_SOURCE;
+use crate::parser::span;
+use crate::unit::errors::{GraphBuildError, UnitLoadError, ValidationIssue};
+use crate::unit::types::{RestartPolicy, UnitFile, UnitType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum DependencyKind {
+    Before,
+    After,
+    Requires,
+    Wants,
+    Conflicts,
+}
+
+/// Forbids units of type `from` from listing units of type `to` under `kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DependencyRule {
+    pub kind: DependencyKind,
+    pub from: UnitType,
+    pub to: UnitType,
+}
+
+impl DependencyRule {
+    /// The rules `check_dependency_types` is usually run with: a target may not require a
+    /// timer.
+    pub fn defaults() -> Vec<DependencyRule> {
+        vec![DependencyRule {
+            kind: DependencyKind::Requires,
+            from: UnitType::Target,
+            to: UnitType::Timer,
+        }]
+    }
+}
+
+/// Fails with `InvalidDependencyType` on the first dependency entry that breaks one of
+/// `rules`. Entries are resolved by unit name or alias; unknown names are ignored.
+pub fn check_dependency_types(
+    units: &[UnitFile],
+    rules: &[DependencyRule],
+) -> Result<(), GraphBuildError> {
+    let mut types: HashMap<&str, UnitType> = HashMap::new();
+    for unit in units {
+        types.insert(&unit.unit.unit_name, unit.unit.unit_type);
+    }
+    for unit in units {
+        for alias in &unit.unit.provides {
+            types.entry(alias).or_insert(unit.unit.unit_type);
+        }
+    }
+
+    for unit in units {
+        let dependency = &unit.dependency;
+        let lists = [
+            (DependencyKind::Before, &dependency.needs_before),
+            (DependencyKind::After, &dependency.needs_after),
+            (DependencyKind::Requires, &dependency.requires),
+            (DependencyKind::Wants, &dependency.wants),
+            (DependencyKind::Conflicts, &dependency.conflicts),
+        ];
+        for (kind, list) in lists {
+            for dep in list {
+                let Some(&to) = types.get(dep.as_str()) else { continue };
+                let rule = DependencyRule { kind, from: unit.unit.unit_type, to };
+                if rules.contains(&rule) {
+                    return Err(GraphBuildError::InvalidDependencyType(
+                        unit.unit.unit_name.clone(),
+                        kind.to_string(),
+                        dep.clone(),
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fails with a `ValidationError` for the first timer or restarting service that lists a
+/// once-only target in any of its dependency lists.
+pub fn check_once_targets(units: &[UnitFile]) -> Result<(), GraphBuildError> {
+    let once: HashMap<&str, &str> = units
+        .iter()
+        .filter(|unit| unit.target.as_ref().is_some_and(|target| target.is_runnable_once))
+        .flat_map(|unit| {
+            let name = unit.unit.unit_name.as_str();
+            std::iter::once((name, name))
+                .chain(unit.unit.provides.iter().map(move |a| (a.as_str(), name)))
+        })
+        .collect();
+
+    for unit in units {
+        let repeats = match unit.unit.unit_type {
+            UnitType::Timer => true,
+            UnitType::Service => unit
+                .service
+                .as_ref()
+                .is_some_and(|service| service.restart_policy != RestartPolicy::No),
+            _ => false,
+        };
+        if !repeats {
+            continue;
+        }
+
+        let mut issues: Vec<ValidationIssue> = unit
+            .dependency
+            .all()
+            .filter_map(|dep| once.get(dep.as_str()))
+            .map(|target| ValidationIssue::OnceTargetDependency(target.to_string()))
+            .collect();
+        issues.dedup();
+        if !issues.is_empty() {
+            let path = unit.origin().unwrap_or(Path::new(INLINE_SOURCE)).to_path_buf();
+            return Err(UnitLoadError::ValidationError(path, span::unlocated(issues)).into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Drops once-only targets from a plan, for regenerating the order after they have run.
+pub fn filter_once_targets(plan: Vec<UnitFile>) -> Vec<UnitFile> {
+    plan.into_iter()
+        .filter(|unit| !unit.target.as_ref().is_some_and(|target| target.is_runnable_once))
+        .collect()
+}
+
+/// Flags with `InertUnit` every unit that lists no dependencies or install targets, is not
+/// named by any other unit, and does nothing when started: a target, or a unit with an empty
+/// `todo.path`. Such units are usually leftover configuration. Units are returned by name, in
+/// input order.
+pub fn lint_units(units: &[UnitFile]) -> Vec<(String, ValidationIssue)> {
+    let referenced: HashSet<&str> = units
+        .iter()
+        .flat_map(|unit| {
+            unit.dependency
+                .all()
+                .chain(&unit.install.wanted_by)
+                .chain(&unit.install.required_by)
+                .filter(move |dep| **dep != unit.unit.unit_name)
+        })
+        .map(String::as_str)
+        .collect();
+
+    units
+        .iter()
+        .filter(|unit| {
+            let idle = unit.unit.unit_type == UnitType::Target || unit.todo.path.trim().is_empty();
+            let unnamed = std::iter::once(&unit.unit.unit_name)
+                .chain(&unit.unit.provides)
+                .all(|name| !referenced.contains(name.as_str()));
+            idle && unnamed && unit.dependency.all().next().is_none() && unit.install.is_empty()
+        })
+        .map(|unit| (unit.unit.unit_name.clone(), ValidationIssue::InertUnit))
+        .collect()
+}
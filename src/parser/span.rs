@@ -0,0 +1,54 @@
+use toml::de::{DeTable, DeValue};
+
+use crate::unit::errors::{LocatedIssue, Location, ValidationIssue};
+
+/// Attaches to each issue the position of the TOML key it is about, or of the closest
+/// enclosing key that is present, such as the section header.
+pub(crate) fn locate(contents: &str, issues: Vec<ValidationIssue>) -> Vec<LocatedIssue> {
+    locate_with(contents, issues, false)
+}
+
+/// Like `locate`, but leaves an issue unlocated unless its full key is present.
+pub(crate) fn locate_exact(contents: &str, issues: Vec<ValidationIssue>) -> Vec<LocatedIssue> {
+    locate_with(contents, issues, true)
+}
+
+fn locate_with(contents: &str, issues: Vec<ValidationIssue>, exact: bool) -> Vec<LocatedIssue> {
+    let Ok(root) = DeTable::parse(contents) else {
+        return unlocated(issues);
+    };
+
+    issues
+        .into_iter()
+        .map(|issue| {
+            let location = find_key(root.get_ref(), issue.key_path())
+                .filter(|&(_, complete)| complete || !exact)
+                .map(|(offset, _)| location_of(contents, offset));
+            LocatedIssue { issue, location }
+        })
+        .collect()
+}
+
+pub(crate) fn unlocated(issues: Vec<ValidationIssue>) -> Vec<LocatedIssue> {
+    issues.into_iter().map(|issue| LocatedIssue { issue, location: None }).collect()
+}
+
+/// Offset of the deepest key of `path` that is present, and whether that is all of `path`.
+fn find_key(table: &DeTable, path: &[&str]) -> Option<(usize, bool)> {
+    let (first, rest) = path.split_first()?;
+    let (key, value) = table.iter().find(|(key, _)| key.get_ref().as_ref() == *first)?;
+    let nested = match value.get_ref() {
+        DeValue::Table(nested) => find_key(nested, rest),
+        _ => None,
+    };
+    nested.or(Some((key.span().start, rest.is_empty())))
+}
+
+fn location_of(contents: &str, offset: usize) -> Location {
+    let before = &contents[..offset.min(contents.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Location {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
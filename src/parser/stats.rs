@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use petgraph::Direction;
+
+use crate::parser::graph::UnitGraph;
+use crate::unit::errors::GraphBuildError;
+use crate::unit::types::{UnitFile, UnitType};
+
+/// Counts over a set of units. `roots` and `leaves` follow the definitions of the `roots`
+/// and `leaves` functions.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UnitStats {
+    pub units: usize,
+    pub by_type: HashMap<UnitType, usize>,
+    pub edges: usize,
+    pub roots: usize,
+    pub leaves: usize,
+}
+
+impl UnitStats {
+    pub fn from_units(units: &[UnitFile]) -> Result<Self, GraphBuildError> {
+        let unit_graph = UnitGraph::from_units(units)?;
+        let graph = unit_graph.inner();
+
+        let mut by_type = HashMap::new();
+        for unit in units {
+            *by_type.entry(unit.unit.unit_type).or_insert(0) += 1;
+        }
+
+        let without_edges = |direction| {
+            graph
+                .node_indices()
+                .filter(|&node| graph.neighbors_directed(node, direction).next().is_none())
+                .count()
+        };
+
+        Ok(Self {
+            units: units.len(),
+            by_type,
+            edges: graph.edge_count(),
+            roots: without_edges(Direction::Outgoing),
+            leaves: without_edges(Direction::Incoming),
+        })
+    }
+
+    pub fn count(&self, unit_type: UnitType) -> usize {
+        self.by_type.get(&unit_type).copied().unwrap_or(0)
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+use tar::{Archive, EntryType};
+
+use crate::parser::loader::{DirScanner, Files, LoadOptions};
+use crate::unit::errors::UnitLoadError;
+use crate::unit::types::UnitFile;
+
+const ARCHIVE_SOURCE: &str = "<archive>";
+
+/// The regular files of an archive, keyed by their normalized path inside it. Includes and
+/// env files are looked up here, never on the host filesystem.
+struct ArchiveFiles(HashMap<PathBuf, Vec<u8>>);
+
+impl Files for ArchiveFiles {
+    fn read(&self, path: &Path) -> io::Result<String> {
+        let bytes = self.0.get(&normalize(path)).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "no such entry in the archive")
+        })?;
+        String::from_utf8(bytes.clone()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn canonical(&self, path: &Path) -> PathBuf {
+        normalize(path)
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<io::Result<PathBuf>>> {
+        let dir = normalize(dir);
+        Ok(self.0.keys().filter(|path| path.parent() == Some(&dir)).cloned().map(Ok).collect())
+    }
+}
+
+/// Resolves `.` and `..` lexically. `..` never climbs above the archive root.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => {}
+        }
+    }
+    normalized
+}
+
+/// Loads every regular file in a tar archive as `load_units` loads a directory, without
+/// unpacking it. Each unit's `origin` is its path inside the archive, and its includes and
+/// env files are read from the archive too. Entries are read in archive order, so the first
+/// of two units with the same name wins.
+pub fn load_units_from_tar(reader: impl Read) -> Result<Vec<UnitFile>, UnitLoadError> {
+    load_units_from_tar_with_options(reader, &LoadOptions::default())
+}
+
+pub fn load_units_from_tar_with_options(
+    reader: impl Read,
+    options: &LoadOptions,
+) -> Result<Vec<UnitFile>, UnitLoadError> {
+    let archive_path = || PathBuf::from(ARCHIVE_SOURCE);
+    let mut archive = Archive::new(reader);
+
+    let mut paths = Vec::new();
+    let mut unreadable = Vec::new();
+    let mut files = ArchiveFiles(HashMap::new());
+    let entries = archive.entries().map_err(|e| UnitLoadError::ReadError(archive_path(), e))?;
+    for entry in entries {
+        let mut entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                unreadable.push((archive_path(), UnitLoadError::DirEntryError(archive_path(), e)));
+                break;
+            }
+        };
+        if entry.header().entry_type() != EntryType::Regular {
+            continue;
+        }
+        let path = match entry.path() {
+            Ok(path) => path.into_owned(),
+            Err(e) => {
+                unreadable.push((archive_path(), UnitLoadError::DirEntryError(archive_path(), e)));
+                continue;
+            }
+        };
+
+        let mut contents = Vec::new();
+        if let Err(e) = entry.read_to_end(&mut contents) {
+            unreadable.push((path.clone(), UnitLoadError::ReadError(path, e)));
+            continue;
+        }
+        files.0.insert(normalize(&path), contents);
+        paths.push(path);
+    }
+
+    let mut scanner = DirScanner::with_files(None, options, &files);
+    for (path, err) in unreadable {
+        scanner.skip(path, err);
+    }
+    for path in paths {
+        scanner.load(path);
+    }
+
+    scanner.finish(&archive_path()).map(|report| report.units)
+}
@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+
+use tokio::fs;
+
+use crate::parser::loader::{DirScanner, Disk, LoadOptions, detect_type, parse_unit_contents};
+use crate::unit::errors::UnitLoadError;
+use crate::unit::types::UnitFile;
+
+/// Async counterpart of `load_unit`. Only reading the unit file goes through `tokio::fs`;
+/// parsing, validation and any `include`/`env_file` reads stay synchronous.
+pub async fn load_unit_async(path: impl AsRef<Path>) -> Result<UnitFile, UnitLoadError> {
+    load_unit_async_with_options(path, &LoadOptions::default()).await
+}
+
+pub async fn load_unit_async_with_options(
+    path: impl AsRef<Path>,
+    options: &LoadOptions,
+) -> Result<UnitFile, UnitLoadError> {
+    let path = path.as_ref();
+    let kind = detect_type(path, &options.registry)?;
+    let contents = fs::read_to_string(path)
+        .await
+        .map_err(|e| UnitLoadError::ReadError(path.to_path_buf(), e))?;
+    parse_unit_contents(path, &contents, kind, options, &[], &Disk)
+}
+
+/// Async counterpart of `load_units`.
+pub async fn load_units_async(dir: impl AsRef<Path>) -> Result<Vec<UnitFile>, UnitLoadError> {
+    load_units_async_with_options(dir, &LoadOptions::default()).await
+}
+
+pub async fn load_units_async_with_options(
+    dir: impl AsRef<Path>,
+    options: &LoadOptions,
+) -> Result<Vec<UnitFile>, UnitLoadError> {
+    let dir = dir.as_ref();
+    let mut scanner = DirScanner::new(None, options);
+
+    let mut entries =
+        fs::read_dir(dir).await.map_err(|e| UnitLoadError::ReadDirError(dir.to_path_buf(), e))?;
+    let mut paths: Vec<PathBuf> = Vec::new();
+    loop {
+        match entries.next_entry().await {
+            Ok(Some(entry)) => paths.push(entry.path()),
+            Ok(None) => break,
+            Err(e) => {
+                scanner.skip(dir.to_path_buf(), UnitLoadError::DirEntryError(dir.to_path_buf(), e));
+            }
+        }
+    }
+    paths.sort();
+
+    for path in paths {
+        if !scanner.wants(&path) {
+            continue;
+        }
+        let result = load_unit_async_with_options(&path, options).await;
+        scanner.accept(path, result);
+    }
+
+    scanner.finish(dir).map(|report| report.units)
+}
@@ -1,2 +1,13 @@
+pub mod builder;
+pub mod command;
+pub mod diff;
+pub mod duration;
 pub mod errors;
+pub mod expand;
+pub mod merge;
+pub mod normalize;
+pub mod registry;
+pub mod set;
+pub mod template;
 pub mod types;
+pub mod validator;
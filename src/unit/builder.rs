@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use crate::unit::errors::ValidationIssue;
+use crate::unit::types::{
+    DependencySection, ServiceSection, SocketSection, TargetSection, TimerSection, TodoSection,
+    UnitFile, UnitSection, UnitType,
+};
+
+#[derive(Debug, Default, Clone)]
+pub struct UnitFileBuilder {
+    name: String,
+    description: Option<String>,
+    unit_type: Option<UnitType>,
+    path: String,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    before: Vec<String>,
+    after: Vec<String>,
+    service: Option<ServiceSection>,
+    target: Option<TargetSection>,
+    timer: Option<TimerSection>,
+    socket: Option<SocketSection>,
+}
+
+impl UnitFileBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn unit_type(mut self, unit_type: UnitType) -> Self {
+        self.unit_type = Some(unit_type);
+        self
+    }
+
+    pub fn exec_path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn before(mut self, unit: impl Into<String>) -> Self {
+        self.before.push(unit.into());
+        self
+    }
+
+    pub fn after(mut self, unit: impl Into<String>) -> Self {
+        self.after.push(unit.into());
+        self
+    }
+
+    pub fn service(mut self, service: ServiceSection) -> Self {
+        self.service = Some(service);
+        self
+    }
+
+    pub fn target(mut self, target: TargetSection) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    pub fn timer(mut self, timer: TimerSection) -> Self {
+        self.timer = Some(timer);
+        self
+    }
+
+    pub fn socket(mut self, socket: SocketSection) -> Self {
+        self.socket = Some(socket);
+        self
+    }
+
+    pub fn build(self) -> Result<UnitFile, Vec<ValidationIssue>> {
+        let Some(unit_type) = self.unit_type else {
+            return Err(vec![ValidationIssue::MissingUnitType]);
+        };
+
+        let unit = UnitFile {
+            unit: UnitSection {
+                unit_name: self.name,
+                description: self.description,
+                unit_type,
+                ..Default::default()
+            },
+            todo: TodoSection {
+                path: self.path,
+                args: self.args,
+                env: self.env,
+                ..Default::default()
+            },
+            service: self.service,
+            target: self.target,
+            timer: self.timer,
+            socket: self.socket,
+            dependency: DependencySection {
+                needs_before: self.before,
+                needs_after: self.after,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        unit.validate()?;
+        Ok(unit)
+    }
+}
@@ -0,0 +1,70 @@
+use crate::unit::errors::ValidationIssue;
+use crate::unit::types::TodoSection;
+
+impl TodoSection {
+    /// Splits `command` into `path` and `args`. Setting `command` alongside `path` or `args`
+    /// is rejected rather than guessing which one wins.
+    pub fn apply_command(&mut self) -> Result<(), ValidationIssue> {
+        let Some(command) = self.command.take() else {
+            return Ok(());
+        };
+
+        if !self.path.is_empty() || !self.args.is_empty() {
+            return Err(ValidationIssue::CommandWithPath);
+        }
+
+        let mut argv = split_command(&command).map_err(ValidationIssue::InvalidCommand)?;
+        if argv.is_empty() {
+            return Err(ValidationIssue::InvalidCommand("command is empty".to_string()));
+        }
+
+        self.path = argv.remove(0);
+        self.args = argv;
+        Ok(())
+    }
+}
+
+/// Splits a command line into words, honoring single quotes, double quotes and backslash
+/// escapes.
+pub(crate) fn split_command(command: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"') | None, '\\') => {
+                let escaped = chars.next().ok_or("trailing backslash")?;
+                current.push(escaped);
+                in_word = true;
+            }
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err("unterminated quote".to_string());
+    }
+    if in_word {
+        words.push(current);
+    }
+
+    Ok(words)
+}
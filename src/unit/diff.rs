@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use crate::unit::types::UnitFile;
+
+/// Differences between two sets of units, matched by name. Every list is sorted by name.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UnitDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<UnitChange>,
+}
+
+/// A unit present in both sets whose contents differ. `sections` names the sections that
+/// changed (`unit`, `todo`, `service`, `target`, `timer`, `socket`, `dependency`,
+/// `condition`, `install`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitChange {
+    pub name: String,
+    pub sections: Vec<&'static str>,
+}
+
+impl UnitDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares `old` against `new`. The file a unit was loaded from is not part of the
+/// comparison, so moving a unit file does not count as a change.
+pub fn diff_units(old: &[UnitFile], new: &[UnitFile]) -> UnitDiff {
+    let by_name = |units: &[UnitFile]| -> HashMap<String, usize> {
+        units.iter().enumerate().map(|(i, u)| (u.unit.unit_name.clone(), i)).collect()
+    };
+    let old_index = by_name(old);
+    let new_index = by_name(new);
+
+    let mut diff = UnitDiff::default();
+
+    for (name, &i) in &new_index {
+        match old_index.get(name) {
+            None => diff.added.push(name.clone()),
+            Some(&j) => {
+                let sections = changed_sections(&old[j], &new[i]);
+                if !sections.is_empty() {
+                    diff.changed.push(UnitChange { name: name.clone(), sections });
+                }
+            }
+        }
+    }
+    diff.removed =
+        old_index.keys().filter(|name| !new_index.contains_key(*name)).cloned().collect();
+
+    diff.added.sort();
+    diff.removed.sort();
+    diff.changed.sort_by(|a, b| a.name.cmp(&b.name));
+    diff
+}
+
+fn changed_sections(old: &UnitFile, new: &UnitFile) -> Vec<&'static str> {
+    let checks = [
+        ("unit", old.unit != new.unit),
+        ("todo", old.todo != new.todo),
+        ("service", old.service != new.service),
+        ("target", old.target != new.target),
+        ("timer", old.timer != new.timer),
+        ("socket", old.socket != new.socket),
+        ("dependency", old.dependency != new.dependency),
+        ("condition", old.condition != new.condition),
+        ("install", old.install != new.install),
+    ];
+    checks.into_iter().filter(|&(_, changed)| changed).map(|(name, _)| name).collect()
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+use std::time::Duration;
+
+use serde::de::{self, Visitor};
+use serde::{Deserializer, Serializer};
+
+const UNITS: [(&[&str], u64); 7] = [
+    (&["us", "usec"], 1),
+    (&["ms", "msec"], 1_000),
+    (&["s", "sec", "second", "seconds"], 1_000_000),
+    (&["m", "min", "minute", "minutes"], 60_000_000),
+    (&["h", "hr", "hour", "hours"], 3_600_000_000),
+    (&["d", "day", "days"], 86_400_000_000),
+    (&["w", "week", "weeks"], 604_800_000_000),
+];
+
+/// Parses systemd-style time spans such as `30s`, `2min` or `1h 30min`. A bare number is
+/// taken as seconds.
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let mut rest = input.trim();
+    if rest.is_empty() {
+        return Err("empty duration".to_string());
+    }
+
+    let mut micros: u64 = 0;
+
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits == 0 {
+            return Err(format!("invalid duration \"{input}\""));
+        }
+        let value: u64 =
+            rest[..digits].parse().map_err(|_| format!("invalid duration \"{input}\""))?;
+        rest = rest[digits..].trim_start();
+
+        let unit_len = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+        let unit = &rest[..unit_len];
+        rest = rest[unit_len..].trim_start();
+
+        let scale = if unit.is_empty() {
+            1_000_000
+        } else {
+            UNITS
+                .iter()
+                .find(|(names, _)| names.contains(&unit))
+                .map(|&(_, scale)| scale)
+                .ok_or_else(|| format!("unknown time unit \"{unit}\" in \"{input}\""))?
+        };
+
+        micros = value
+            .checked_mul(scale)
+            .and_then(|v| micros.checked_add(v))
+            .ok_or_else(|| format!("duration \"{input}\" is too large"))?;
+    }
+
+    Ok(Duration::from_micros(micros))
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_micros();
+    if !micros.is_multiple_of(1_000_000) {
+        return if micros.is_multiple_of(1_000) {
+            format!("{}ms", micros / 1_000)
+        } else {
+            format!("{micros}us")
+        };
+    }
+
+    let mut secs = duration.as_secs();
+    if secs == 0 {
+        return "0s".to_string();
+    }
+
+    let mut parts = Vec::new();
+    for (unit, size) in [("d", 86_400), ("h", 3_600), ("min", 60), ("s", 1)] {
+        if secs >= size {
+            parts.push(format!("{}{}", secs / size, unit));
+            secs %= size;
+        }
+    }
+    parts.join(" ")
+}
+
+pub(crate) mod option {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(duration) => serializer.serialize_str(&format_duration(*duration)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        deserializer.deserialize_any(DurationVisitor).map(Some)
+    }
+}
+
+struct DurationVisitor;
+
+impl Visitor<'_> for DurationVisitor {
+    type Value = Duration;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a duration such as \"30s\" or \"2min\", or a number of seconds")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Duration, E> {
+        parse_duration(value).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Duration, E> {
+        Ok(Duration::from_secs(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Duration, E> {
+        u64::try_from(value)
+            .map(Duration::from_secs)
+            .map_err(|_| E::custom(format!("duration cannot be negative: {value}")))
+    }
+}
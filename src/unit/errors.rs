@@ -1,7 +1,10 @@
+use std::fmt;
 use std::path::PathBuf;
 
 use thiserror::Error;
 
+use crate::unit::types::UnitType;
+
 #[derive(Debug, Error)]
 pub enum UnitLoadError {
     #[error("Missing or invalid file extension")]
@@ -16,8 +19,17 @@ pub enum UnitLoadError {
     #[error("Invalid unit format in file {0}: {1}")]
     ParseError(PathBuf, #[source] toml::de::Error),
 
-    #[error("Validation failed or unit type mismatch in {0}")]
-    ValidationError(PathBuf),
+    #[error("Invalid JSON unit format in file {0}: {1}")]
+    JsonParseError(PathBuf, #[source] serde_json::Error),
+
+    #[error("Invalid systemd unit format in file {0}: {1}")]
+    IniParseError(PathBuf, String),
+
+    #[error("Validation failed in {0}: {issues}", issues = join_issues(.1))]
+    ValidationError(PathBuf, Vec<LocatedIssue>),
+
+    #[error("Unit type mismatch in {path}: extension implies {expected}, file declares {found}")]
+    TypeMismatch { path: PathBuf, expected: UnitType, found: UnitType },
 
     #[error("Failed to read unit directory {0}: {1}")]
     ReadDirError(PathBuf, #[source] std::io::Error),
@@ -25,8 +37,75 @@ pub enum UnitLoadError {
     #[error("Invalid unit file entry in directory {0}: {1}")]
     DirEntryError(PathBuf, #[source] std::io::Error),
 
+    #[error("No units found in {0}")]
+    NoUnitsFound(PathBuf),
+
     #[error("\"{0}\" depends on missing unit \"{1}\"")]
     MissingDependency(String, String),
+
+    #[error("Unit \"{0}\" is declared by both {1} and {2}")]
+    DuplicateUnitName(String, PathBuf, PathBuf),
+
+    #[error("\"{0}\" references undefined variable \"{1}\"")]
+    UndefinedVariable(String, String),
+
+    #[error("\"{0}\" contains an unterminated ${{...}} reference")]
+    UnterminatedVariable(String),
+
+    #[error("Invalid glob pattern \"{0}\": {1}")]
+    InvalidGlobPattern(String, #[source] glob::PatternError),
+
+    #[error("Unknown field \"{1}\" in {0}")]
+    UnknownField(PathBuf, String),
+
+    #[error("Failed to read environment file {1} for {0}: {2}")]
+    EnvFileError(PathBuf, PathBuf, #[source] std::io::Error),
+
+    #[error("Invalid line {1} in environment file {0}: expected KEY=VALUE")]
+    InvalidEnvFileLine(PathBuf, usize),
+
+    #[error("Include cycle detected: {path}", path = render_paths(.0))]
+    IncludeCycle(Vec<PathBuf>),
+}
+
+/// Broad failure class of a [`UnitLoadError`], e.g. to retry IO errors but reject parse errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// Reading a file or directory failed.
+    Io,
+    /// The file contents could not be parsed.
+    Parse,
+    /// The unit parsed but is invalid on its own or among its siblings.
+    Validation,
+    /// The file extension or declared unit type is wrong.
+    Type,
+}
+
+impl UnitLoadError {
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            UnitLoadError::ReadError(..)
+            | UnitLoadError::ReadDirError(..)
+            | UnitLoadError::DirEntryError(..)
+            | UnitLoadError::EnvFileError(..) => ErrorCategory::Io,
+            UnitLoadError::ParseError(..)
+            | UnitLoadError::JsonParseError(..)
+            | UnitLoadError::IniParseError(..)
+            | UnitLoadError::UndefinedVariable(..)
+            | UnitLoadError::UnterminatedVariable(..)
+            | UnitLoadError::InvalidGlobPattern(..)
+            | UnitLoadError::UnknownField(..)
+            | UnitLoadError::InvalidEnvFileLine(..)
+            | UnitLoadError::IncludeCycle(..) => ErrorCategory::Parse,
+            UnitLoadError::ValidationError(..)
+            | UnitLoadError::NoUnitsFound(..)
+            | UnitLoadError::MissingDependency(..)
+            | UnitLoadError::DuplicateUnitName(..) => ErrorCategory::Validation,
+            UnitLoadError::InvalidExtension
+            | UnitLoadError::UnsupportedUnitType(..)
+            | UnitLoadError::TypeMismatch { .. } => ErrorCategory::Type,
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -34,6 +113,205 @@ pub enum GraphBuildError {
     #[error(transparent)]
     LoadError(#[from] UnitLoadError),
 
-    #[error("Cycle detected involving: {0}")]
-    DependencyCycle(String),
+    #[error("Cycle detected: {path}", path = render_cycle(.0))]
+    DependencyCycle(Vec<String>),
+
+    #[error("\"{0}\" conflicts with \"{1}\" but both are present")]
+    ConflictViolation(String, String),
+
+    #[error("\"{0}\" is ordered both before and after \"{1}\"")]
+    ContradictoryOrdering(String, String),
+
+    #[error("Unknown unit \"{0}\"")]
+    UnknownUnit(String),
+
+    #[error("\"{0}\" is not a target")]
+    NotATarget(String),
+
+    #[error("Alias \"{0}\" is provided by both \"{1}\" and \"{2}\"")]
+    AmbiguousAlias(String, String, String),
+
+    #[error("\"{0}\" both requires and conflicts with \"{1}\"")]
+    RequiresAndConflicts(String, String),
+
+    #[error("\"{0}\" requires masked unit \"{1}\"")]
+    MaskedDependency(String, String),
+
+    #[error("\"{0}\" {1} \"{2}\", whose unit type is not allowed there")]
+    InvalidDependencyType(String, String, String),
+
+    #[error("\"{0}\" ends an ordering chain longer than {1} units")]
+    DepthLimitExceeded(String, usize),
+
+    #[error("\"{0}\" requires \"{1}\", whose conditions are not met")]
+    ConditionFailed(String, String),
+
+    #[error("\"{0}\" is already in the graph")]
+    UnitExists(String),
+
+    #[error("Failed to serialize plan: {0}")]
+    PlanSerializeError(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    #[error("Unit name cannot be empty")]
+    EmptyUnitName,
+
+    #[error("Invalid unit name \"{0}\": only letters, digits, '.', '_', '-' and '@' are allowed")]
+    InvalidUnitName(String),
+
+    #[error("Todo path cannot be empty")]
+    EmptyTodoPath,
+
+    #[error("Pre command {0} has an empty path")]
+    EmptyPreCommandPath(usize),
+
+    #[error("Post command {0} has an empty path")]
+    EmptyPostCommandPath(usize),
+
+    #[error("Invalid environment variable name \"{0}\": expected [A-Za-z_][A-Za-z0-9_]*")]
+    InvalidEnvKey(String),
+
+    #[error("Service unit requires [service] section")]
+    MissingServiceSection,
+
+    #[error("Target unit requires [target] section")]
+    MissingTargetSection,
+
+    #[error("Timer unit requires [timer] section")]
+    MissingTimerSection,
+
+    #[error("Timer unit needs at least one of on_calendar, on_boot_sec or on_unit_active_sec")]
+    EmptyTimerSchedule,
+
+    #[error("Socket unit requires [socket] section")]
+    MissingSocketSection,
+
+    #[error("Socket unit needs at least one listen_stream or listen_datagram address")]
+    EmptySocketListen,
+
+    #[error("Socket service name cannot be empty")]
+    EmptySocketService,
+
+    /// Index into the `before`, `after`, `requires`, `wants` and `conflicts` entries, in
+    /// that order.
+    #[error("Dependency name cannot be empty (entry {0})")]
+    EmptyDependencyName(usize),
+
+    #[error("Unit cannot depend on itself")]
+    SelfDependency,
+
+    #[error("Unit both requires and conflicts with \"{0}\"")]
+    RequiresAndConflicts(String),
+
+    #[error("Unit type must be set")]
+    MissingUnitType,
+
+    #[error("Todo command cannot be combined with path or args")]
+    CommandWithPath,
+
+    #[error("Invalid todo command: {0}")]
+    InvalidCommand(String),
+
+    #[error("Service {0} must be greater than zero")]
+    ZeroTimeout(&'static str),
+
+    #[error("Working directory {} must be absolute", .0.display())]
+    RelativeWorkingDirectory(PathBuf),
+
+    #[error("Timers and restarting services cannot depend on once-only target \"{0}\"")]
+    OnceTargetDependency(String),
+
+    #[error("Unit description cannot be empty")]
+    MissingDescription,
+
+    #[error("Todo path \"{0}\" must be absolute")]
+    RelativeExecPath(String),
+
+    #[error("Unit has no dependencies, is not a dependency of any unit and performs no action")]
+    InertUnit,
+
+    #[error("Executable \"{0}\" does not exist")]
+    ExecutableNotFound(String),
+
+    #[error("\"{0}\" is not an executable file")]
+    NotExecutable(String),
+
+    /// Raised by user-supplied [`Validator`](crate::Validator)s.
+    #[error("{0}")]
+    Custom(String),
+}
+
+fn render_cycle(names: &[String]) -> String {
+    let mut path = names.to_vec();
+    if let Some(first) = names.first() {
+        path.push(first.clone());
+    }
+    path.join(" -> ")
+}
+
+fn render_paths(paths: &[PathBuf]) -> String {
+    paths.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(" -> ")
+}
+
+/// 1-based position in a unit file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A validation issue with the position of the key it concerns, when the unit was loaded
+/// from TOML and the key (or its section) is present in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocatedIssue {
+    pub issue: ValidationIssue,
+    pub location: Option<Location>,
+}
+
+impl fmt::Display for LocatedIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some(Location { line, column }) => write!(f, "{line}:{column}: {}", self.issue),
+            None => write!(f, "{}", self.issue),
+        }
+    }
+}
+
+impl ValidationIssue {
+    /// Dotted TOML key the issue refers to, or an empty slice when it has no single key.
+    pub fn key_path(&self) -> &'static [&'static str] {
+        match self {
+            ValidationIssue::EmptyUnitName => &["unit", "name"],
+            ValidationIssue::EmptyTodoPath => &["todo", "path"],
+            ValidationIssue::EmptyPreCommandPath(_) => &["todo", "pre"],
+            ValidationIssue::EmptyPostCommandPath(_) => &["todo", "post"],
+            ValidationIssue::InvalidEnvKey(_) => &["todo", "env"],
+            ValidationIssue::CommandWithPath | ValidationIssue::InvalidCommand(_) => {
+                &["todo", "command"]
+            }
+            ValidationIssue::MissingServiceSection
+            | ValidationIssue::MissingTargetSection
+            | ValidationIssue::MissingTimerSection
+            | ValidationIssue::MissingSocketSection
+            | ValidationIssue::MissingUnitType => &["unit", "type"],
+            ValidationIssue::ZeroTimeout("start_timeout") => &["service", "start_timeout"],
+            ValidationIssue::ZeroTimeout(_) => &["service", "stop_timeout"],
+            ValidationIssue::RelativeWorkingDirectory(_) => &["service", "workdir"],
+            ValidationIssue::EmptyTimerSchedule => &["timer"],
+            ValidationIssue::EmptySocketListen => &["socket"],
+            ValidationIssue::EmptySocketService => &["socket", "service"],
+            ValidationIssue::EmptyDependencyName(_)
+            | ValidationIssue::SelfDependency
+            | ValidationIssue::RequiresAndConflicts(_) => &["dependency"],
+            ValidationIssue::MissingDescription => &["unit", "description"],
+            ValidationIssue::RelativeExecPath(_) => &["todo", "path"],
+            _ => &[],
+        }
+    }
+}
+
+fn join_issues(issues: &[LocatedIssue]) -> String {
+    issues.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
 }
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use crate::unit::errors::UnitLoadError;
+use crate::unit::types::UnitFile;
+
+/// What `expand_env` does with a reference to a variable missing from the map.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UndefinedVars {
+    #[default]
+    Error,
+    Empty,
+}
+
+impl UnitFile {
+    /// Expands `${VAR}` and `$VAR` in `todo.path`, `todo.args`, the pre/post commands and
+    /// `todo.env` values, with `$$` producing a literal `$`. Substituted values are not
+    /// expanded again. Pass `std::env::vars().collect()` to expand from the process
+    /// environment.
+    pub fn expand_env(
+        &mut self,
+        vars: &HashMap<String, String>,
+        undefined: UndefinedVars,
+    ) -> Result<(), UnitLoadError> {
+        let name = &self.unit.unit_name;
+        let todo = &mut self.todo;
+
+        todo.path = expand(&todo.path, vars, undefined, name)?;
+        for arg in &mut todo.args {
+            *arg = expand(arg, vars, undefined, name)?;
+        }
+        for command in todo.pre.iter_mut().chain(&mut todo.post) {
+            command.path = expand(&command.path, vars, undefined, name)?;
+            for arg in &mut command.args {
+                *arg = expand(arg, vars, undefined, name)?;
+            }
+        }
+        for value in todo.env.values_mut() {
+            *value = expand(value, vars, undefined, name)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn expand(
+    input: &str,
+    vars: &HashMap<String, String>,
+    undefined: UndefinedVars,
+    unit: &str,
+) -> Result<String, UnitLoadError> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+
+        let (var, tail) = if let Some(tail) = rest.strip_prefix('$') {
+            out.push('$');
+            rest = tail;
+            continue;
+        } else if let Some(braced) = rest.strip_prefix('{') {
+            let end = braced
+                .find('}')
+                .ok_or_else(|| UnitLoadError::UnterminatedVariable(unit.to_string()))?;
+            (&braced[..end], &braced[end + 1..])
+        } else {
+            let end = rest
+                .char_indices()
+                .find(|&(i, c)| {
+                    !(c == '_' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()))
+                })
+                .map_or(rest.len(), |(i, _)| i);
+            if end == 0 {
+                out.push('$');
+                continue;
+            }
+            (&rest[..end], &rest[end..])
+        };
+
+        match (vars.get(var), undefined) {
+            (Some(value), _) => out.push_str(value),
+            (None, UndefinedVars::Empty) => {}
+            (None, UndefinedVars::Error) => {
+                return Err(UnitLoadError::UndefinedVariable(unit.to_string(), var.to_string()));
+            }
+        }
+        rest = tail;
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::unit::duration;
+use crate::unit::types::{ExecCommand, RestartPolicy, UnitFile};
+
+/// How [`UnitFile::merge_with`] combines list fields such as dependencies and socket
+/// addresses.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Append entries that are not already present.
+    #[default]
+    Union,
+    /// Use the other unit's list in place of the current one.
+    Replace,
+}
+
+/// A unit fragment in which every field is optional, as found in override files. The unit's
+/// name and type cannot be overridden.
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct PartialUnitFile {
+    pub unit: Option<PartialUnitSection>,
+    pub todo: Option<PartialTodoSection>,
+    pub service: Option<PartialServiceSection>,
+    pub target: Option<PartialTargetSection>,
+    pub timer: Option<PartialTimerSection>,
+    pub socket: Option<PartialSocketSection>,
+    pub dependency: Option<PartialDependencySection>,
+    pub condition: Option<PartialConditionSection>,
+    pub install: Option<PartialInstallSection>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct PartialUnitSection {
+    pub description: Option<String>,
+    pub provides: Option<Vec<String>>,
+    pub default_dependencies: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct PartialTodoSection {
+    pub path: Option<String>,
+    pub args: Option<Vec<String>>,
+    pub env: HashMap<String, String>,
+    pub env_file: Option<Vec<String>>,
+    pub command: Option<String>,
+    pub pre: Option<Vec<ExecCommand>>,
+    pub post: Option<Vec<ExecCommand>>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct PartialServiceSection {
+    #[serde(rename = "restart")]
+    pub restart_policy: Option<RestartPolicy>,
+
+    #[serde(rename = "restart_command")]
+    pub command_on_restart: Option<String>,
+
+    #[serde(rename = "stop")]
+    pub command_on_stop: Option<String>,
+
+    #[serde(rename = "workdir")]
+    pub working_directory: Option<PathBuf>,
+
+    pub user: Option<String>,
+
+    pub group: Option<String>,
+
+    #[serde(with = "duration::option")]
+    pub start_timeout: Option<Duration>,
+
+    #[serde(with = "duration::option")]
+    pub stop_timeout: Option<Duration>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct PartialTargetSection {
+    #[serde(rename = "once")]
+    pub is_runnable_once: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct PartialTimerSection {
+    pub on_calendar: Option<String>,
+
+    #[serde(with = "duration::option")]
+    pub on_boot_sec: Option<Duration>,
+
+    #[serde(with = "duration::option")]
+    pub on_unit_active_sec: Option<Duration>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct PartialSocketSection {
+    pub listen_stream: Option<Vec<String>>,
+    pub listen_datagram: Option<Vec<String>>,
+    pub service: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct PartialDependencySection {
+    #[serde(rename = "before")]
+    pub needs_before: Option<Vec<String>>,
+
+    #[serde(rename = "after")]
+    pub needs_after: Option<Vec<String>>,
+
+    pub requires: Option<Vec<String>>,
+    pub wants: Option<Vec<String>>,
+    pub conflicts: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct PartialConditionSection {
+    pub path_exists: Option<Vec<String>>,
+    pub path_not_exists: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct PartialInstallSection {
+    pub wanted_by: Option<Vec<String>>,
+    pub required_by: Option<Vec<String>>,
+}
+
+impl From<&UnitFile> for PartialUnitFile {
+    fn from(unit: &UnitFile) -> Self {
+        Self {
+            unit: Some(PartialUnitSection {
+                description: unit.unit.description.clone(),
+                provides: Some(unit.unit.provides.clone()),
+                default_dependencies: Some(unit.unit.default_dependencies),
+            }),
+            todo: Some(PartialTodoSection {
+                path: Some(unit.todo.path.clone()),
+                args: Some(unit.todo.args.clone()),
+                env: unit.todo.env.clone(),
+                env_file: Some(unit.todo.env_file.clone()),
+                command: unit.todo.command.clone(),
+                pre: Some(unit.todo.pre.clone()),
+                post: Some(unit.todo.post.clone()),
+            }),
+            service: unit.service.as_ref().map(|service| PartialServiceSection {
+                restart_policy: Some(service.restart_policy),
+                command_on_restart: service.command_on_restart.clone(),
+                command_on_stop: service.command_on_stop.clone(),
+                working_directory: service.working_directory.clone(),
+                user: service.user.clone(),
+                group: service.group.clone(),
+                start_timeout: service.start_timeout,
+                stop_timeout: service.stop_timeout,
+            }),
+            target: unit.target.as_ref().map(|target| PartialTargetSection {
+                is_runnable_once: Some(target.is_runnable_once),
+            }),
+            timer: unit.timer.as_ref().map(|timer| PartialTimerSection {
+                on_calendar: timer.on_calendar.clone(),
+                on_boot_sec: timer.on_boot_sec,
+                on_unit_active_sec: timer.on_unit_active_sec,
+            }),
+            socket: unit.socket.as_ref().map(|socket| PartialSocketSection {
+                listen_stream: Some(socket.listen_stream.clone()),
+                listen_datagram: Some(socket.listen_datagram.clone()),
+                service: Some(socket.service.clone()),
+            }),
+            dependency: Some(PartialDependencySection {
+                needs_before: Some(unit.dependency.needs_before.clone()),
+                needs_after: Some(unit.dependency.needs_after.clone()),
+                requires: Some(unit.dependency.requires.clone()),
+                wants: Some(unit.dependency.wants.clone()),
+                conflicts: Some(unit.dependency.conflicts.clone()),
+            }),
+            condition: Some(PartialConditionSection {
+                path_exists: Some(unit.condition.path_exists.clone()),
+                path_not_exists: Some(unit.condition.path_not_exists.clone()),
+            }),
+            install: Some(PartialInstallSection {
+                wanted_by: Some(unit.install.wanted_by.clone()),
+                required_by: Some(unit.install.required_by.clone()),
+            }),
+        }
+    }
+}
+
+impl UnitFile {
+    /// Overlays `other` onto this unit, unioning list fields. See [`UnitFile::merge_with`].
+    pub fn merge(&mut self, other: &PartialUnitFile) {
+        self.merge_with(other, MergeStrategy::Union);
+    }
+
+    /// Overlays `other` onto this unit. Scalar fields set in `other` (the description, todo
+    /// path and args, service commands and restart policy, timer schedules, ...) overwrite the
+    /// current value; unset ones are left alone. `todo.env` is merged key by key with `other`
+    /// winning. Dependency, install and condition lists, aliases, env files and socket
+    /// addresses follow `strategy`. A section given in
+    /// `other` but missing here is created from its defaults first. A full `UnitFile` can be
+    /// merged in through `PartialUnitFile::from`.
+    pub fn merge_with(&mut self, other: &PartialUnitFile, strategy: MergeStrategy) {
+        let merge =
+            |list: &mut Vec<String>, extra: &Option<Vec<String>>| merge_list(list, extra, strategy);
+
+        if let Some(unit) = &other.unit {
+            overwrite(&mut self.unit.description, &unit.description);
+            merge(&mut self.unit.provides, &unit.provides);
+            set(&mut self.unit.default_dependencies, &unit.default_dependencies);
+        }
+
+        if let Some(todo) = &other.todo {
+            set(&mut self.todo.path, &todo.path);
+            set(&mut self.todo.args, &todo.args);
+            merge(&mut self.todo.env_file, &todo.env_file);
+            overwrite(&mut self.todo.command, &todo.command);
+            set(&mut self.todo.pre, &todo.pre);
+            set(&mut self.todo.post, &todo.post);
+            self.todo.env.extend(todo.env.clone());
+        }
+
+        if let Some(service) = &other.service {
+            let section = self.service.get_or_insert_with(Default::default);
+            set(&mut section.restart_policy, &service.restart_policy);
+            overwrite(&mut section.command_on_restart, &service.command_on_restart);
+            overwrite(&mut section.command_on_stop, &service.command_on_stop);
+            overwrite(&mut section.working_directory, &service.working_directory);
+            overwrite(&mut section.user, &service.user);
+            overwrite(&mut section.group, &service.group);
+            overwrite(&mut section.start_timeout, &service.start_timeout);
+            overwrite(&mut section.stop_timeout, &service.stop_timeout);
+        }
+
+        if let Some(target) = &other.target {
+            let section = self.target.get_or_insert_with(Default::default);
+            set(&mut section.is_runnable_once, &target.is_runnable_once);
+        }
+
+        if let Some(timer) = &other.timer {
+            let section = self.timer.get_or_insert_with(Default::default);
+            overwrite(&mut section.on_calendar, &timer.on_calendar);
+            overwrite(&mut section.on_boot_sec, &timer.on_boot_sec);
+            overwrite(&mut section.on_unit_active_sec, &timer.on_unit_active_sec);
+        }
+
+        if let Some(socket) = &other.socket {
+            let section = self.socket.get_or_insert_with(Default::default);
+            merge(&mut section.listen_stream, &socket.listen_stream);
+            merge(&mut section.listen_datagram, &socket.listen_datagram);
+            set(&mut section.service, &socket.service);
+        }
+
+        if let Some(dependency) = &other.dependency {
+            let section = &mut self.dependency;
+            merge(&mut section.needs_before, &dependency.needs_before);
+            merge(&mut section.needs_after, &dependency.needs_after);
+            merge(&mut section.requires, &dependency.requires);
+            merge(&mut section.wants, &dependency.wants);
+            merge(&mut section.conflicts, &dependency.conflicts);
+        }
+
+        if let Some(condition) = &other.condition {
+            let section = &mut self.condition;
+            merge(&mut section.path_exists, &condition.path_exists);
+            merge(&mut section.path_not_exists, &condition.path_not_exists);
+        }
+
+        if let Some(install) = &other.install {
+            let section = &mut self.install;
+            merge(&mut section.wanted_by, &install.wanted_by);
+            merge(&mut section.required_by, &install.required_by);
+        }
+    }
+}
+
+fn set<T: Clone>(field: &mut T, value: &Option<T>) {
+    if let Some(value) = value {
+        *field = value.clone();
+    }
+}
+
+fn overwrite<T: Clone>(field: &mut Option<T>, value: &Option<T>) {
+    if value.is_some() {
+        field.clone_from(value);
+    }
+}
+
+fn merge_list(list: &mut Vec<String>, extra: &Option<Vec<String>>, strategy: MergeStrategy) {
+    if strategy == MergeStrategy::Replace
+        && let Some(extra) = extra
+    {
+        list.clone_from(extra);
+        return;
+    }
+
+    for entry in extra.iter().flatten() {
+        if !list.contains(entry) {
+            list.push(entry.clone());
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+use crate::unit::types::UnitFile;
+
+impl UnitFile {
+    /// Trims names as `trim_names` does and drops repeated entries from each dependency list,
+    /// keeping the first occurrence.
+    pub fn normalize(&mut self) {
+        self.trim_names();
+
+        let dependency = &mut self.dependency;
+        for list in [
+            &mut dependency.needs_before,
+            &mut dependency.needs_after,
+            &mut dependency.requires,
+            &mut dependency.wants,
+            &mut dependency.conflicts,
+        ] {
+            let mut seen = HashSet::new();
+            list.retain(|dep| seen.insert(dep.clone()));
+        }
+    }
+
+    /// Joins relative `todo.path`, pre/post command paths and `env_file` entries onto
+    /// `base`, usually the directory of the unit's origin. Absolute paths and bare program
+    /// names such as `sh`, which are looked up in `PATH`, are left untouched, as is
+    /// everything when `base` is empty. `.` components are dropped; nothing is
+    /// canonicalized, so the paths need not exist yet.
+    pub fn resolve_paths(&mut self, base: &Path) {
+        if base.as_os_str().is_empty() {
+            return;
+        }
+
+        let todo = &mut self.todo;
+        resolve_program(&mut todo.path, base);
+        for command in todo.pre.iter_mut().chain(&mut todo.post) {
+            resolve_program(&mut command.path, base);
+        }
+        for entry in &mut todo.env_file {
+            match entry.strip_prefix('-') {
+                Some(file) => {
+                    let mut file = file.to_string();
+                    resolve(&mut file, base);
+                    *entry = format!("-{file}");
+                }
+                None => resolve(entry, base),
+            }
+        }
+    }
+
+    /// Strips surrounding whitespace from the unit name, its aliases, every dependency and
+    /// install entry and the socket's service name. Returns the original values that changed.
+    pub fn trim_names(&mut self) -> Vec<String> {
+        let mut trimmed = Vec::new();
+        let mut trim = |name: &mut String| {
+            if name.trim().len() != name.len() {
+                trimmed.push(std::mem::replace(name, name.trim().to_string()));
+            }
+        };
+
+        trim(&mut self.unit.unit_name);
+        self.unit.provides.iter_mut().for_each(&mut trim);
+
+        let dependency = &mut self.dependency;
+        for list in [
+            &mut dependency.needs_before,
+            &mut dependency.needs_after,
+            &mut dependency.requires,
+            &mut dependency.wants,
+            &mut dependency.conflicts,
+        ] {
+            list.iter_mut().for_each(&mut trim);
+        }
+        self.install.wanted_by.iter_mut().for_each(&mut trim);
+        self.install.required_by.iter_mut().for_each(&mut trim);
+
+        if let Some(socket) = &mut self.socket {
+            trim(&mut socket.service);
+        }
+
+        trimmed
+    }
+}
+
+fn resolve_program(path: &mut String, base: &Path) {
+    if Path::new(path.as_str()).components().count() > 1 {
+        resolve(path, base);
+    }
+}
+
+fn resolve(path: &mut String, base: &Path) {
+    let relative = Path::new(path.as_str());
+    if relative.is_absolute() {
+        return;
+    }
+    let resolved: PathBuf =
+        base.join(relative).components().filter(|c| *c != Component::CurDir).collect();
+    *path = resolved.to_string_lossy().into_owned();
+}
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use strum::IntoEnumIterator;
+
+use crate::unit::types::UnitType;
+
+/// Maps file extensions to the unit type they are loaded as. Every built-in type is registered
+/// under its own name; extra extensions such as `job` or `mount` can be registered on top and
+/// are parsed and validated as the type they map to.
+#[derive(Debug, Clone)]
+pub struct UnitTypeRegistry {
+    extensions: HashMap<String, UnitType>,
+}
+
+impl Default for UnitTypeRegistry {
+    fn default() -> Self {
+        Self { extensions: UnitType::iter().map(|ty| (ty.to_string(), ty)).collect() }
+    }
+}
+
+/// Uses exactly the given extensions, without the built-in ones.
+impl From<HashMap<String, UnitType>> for UnitTypeRegistry {
+    fn from(extensions: HashMap<String, UnitType>) -> Self {
+        Self { extensions }
+    }
+}
+
+impl UnitTypeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `extension` (without the leading dot), returning the type it previously
+    /// mapped to.
+    pub fn register(
+        &mut self,
+        extension: impl Into<String>,
+        unit_type: UnitType,
+    ) -> Option<UnitType> {
+        self.extensions.insert(extension.into(), unit_type)
+    }
+
+    pub fn unregister(&mut self, extension: &str) -> Option<UnitType> {
+        self.extensions.remove(extension)
+    }
+
+    pub fn resolve(&self, extension: &str) -> Option<UnitType> {
+        self.extensions.get(extension).copied()
+    }
+
+    pub fn extensions(&self) -> impl Iterator<Item = (&str, UnitType)> {
+        self.extensions.iter().map(|(ext, ty)| (ext.as_str(), *ty))
+    }
+}
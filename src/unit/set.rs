@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use crate::unit::errors::UnitLoadError;
+use crate::unit::types::UnitFile;
+
+/// Units indexed by name for constant-time lookup. Keeps the order they were added in.
+#[derive(Debug, Default, Clone)]
+pub struct UnitSet {
+    units: Vec<UnitFile>,
+    index: HashMap<String, usize>,
+}
+
+impl UnitSet {
+    /// Fails with `DuplicateUnitName` if two units share a name.
+    pub fn from_vec(units: Vec<UnitFile>) -> Result<Self, UnitLoadError> {
+        let mut index = HashMap::with_capacity(units.len());
+        for (i, unit) in units.iter().enumerate() {
+            if let Some(&first) = index.get(&unit.unit.unit_name) {
+                let origin = |unit: &UnitFile| unit.origin.clone().unwrap_or_default();
+                return Err(UnitLoadError::DuplicateUnitName(
+                    unit.unit.unit_name.clone(),
+                    origin(&units[first]),
+                    origin(unit),
+                ));
+            }
+            index.insert(unit.unit.unit_name.clone(), i);
+        }
+
+        Ok(Self { units, index })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&UnitFile> {
+        self.index.get(name).map(|&i| &self.units[i])
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.units.iter().map(|unit| unit.unit.unit_name.as_str())
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, UnitFile> {
+        self.units.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[UnitFile] {
+        &self.units
+    }
+
+    pub fn into_vec(self) -> Vec<UnitFile> {
+        self.units
+    }
+}
+
+impl IntoIterator for UnitSet {
+    type Item = UnitFile;
+    type IntoIter = std::vec::IntoIter<UnitFile>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.units.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a UnitSet {
+    type Item = &'a UnitFile;
+    type IntoIter = std::slice::Iter<'a, UnitFile>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.units.iter()
+    }
+}
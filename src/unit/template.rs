@@ -0,0 +1,59 @@
+use crate::unit::types::UnitFile;
+
+const TEMPLATE_MARKER: char = '@';
+
+pub(crate) const INSTANCE_SPECIFIER: &str = "%i";
+
+impl UnitFile {
+    /// Whether this is a template such as `getty@` or `getty@.service`: the name has an `@`
+    /// with no instance between it and the first `.` after it.
+    pub fn is_template(&self) -> bool {
+        self.unit
+            .unit_name
+            .split_once(TEMPLATE_MARKER)
+            .is_some_and(|(_, rest)| rest.is_empty() || rest.starts_with('.'))
+    }
+
+    /// Returns a copy named for `instance` (`getty@` becomes `getty@tty1`) with `%i` replaced
+    /// by `instance` in the exec paths, args, env values and dependency names. Units that are
+    /// not templates are returned unchanged.
+    pub fn instantiate(&self, instance: &str) -> UnitFile {
+        let mut unit = self.clone();
+        if !self.is_template() {
+            return unit;
+        }
+
+        if let Some(at) = unit.unit.unit_name.find(TEMPLATE_MARKER) {
+            unit.unit.unit_name.insert_str(at + 1, instance);
+        }
+
+        let expand = |value: &mut String| *value = value.replace(INSTANCE_SPECIFIER, instance);
+
+        let todo = &mut unit.todo;
+        expand(&mut todo.path);
+        todo.args.iter_mut().for_each(expand);
+        for command in todo.pre.iter_mut().chain(&mut todo.post) {
+            expand(&mut command.path);
+            command.args.iter_mut().for_each(expand);
+        }
+        todo.env.values_mut().for_each(expand);
+
+        let dependency = &mut unit.dependency;
+        for list in [
+            &mut dependency.needs_before,
+            &mut dependency.needs_after,
+            &mut dependency.requires,
+            &mut dependency.wants,
+            &mut dependency.conflicts,
+        ] {
+            list.iter_mut().for_each(expand);
+        }
+        unit.install.wanted_by.iter_mut().for_each(expand);
+        unit.install.required_by.iter_mut().for_each(expand);
+        if let Some(socket) = &mut unit.socket {
+            expand(&mut socket.service);
+        }
+
+        unit
+    }
+}
@@ -1,17 +1,78 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use serde::Deserialize;
-use strum_macros::{Display, EnumString};
+use serde::{Deserialize, Serialize, Serializer};
+use strum::VariantArray;
+use strum_macros::{Display, EnumIter, EnumString, IntoStaticStr, VariantArray};
 
-#[derive(Debug, Deserialize, PartialEq, Eq, EnumString, Display, Clone, Copy)]
+use crate::parser::loader::detect_type;
+use crate::unit::duration;
+use crate::unit::errors::{UnitLoadError, ValidationIssue};
+use crate::unit::registry::UnitTypeRegistry;
+use crate::unit::template::INSTANCE_SPECIFIER;
+
+/// Characters allowed in unit names and dependency references, besides ASCII letters and
+/// digits. Names also may not contain empty `.`-separated segments, so `a..service` and
+/// `.service` are rejected.
+pub const UNIT_NAME_PUNCTUATION: &[char] = &['.', '_', '-', '@'];
+
+/// Glob characters that turn a `before` or `after` entry into a pattern, such as
+/// `*.service` or `net-*`, matched against the names of the loaded units. A name also
+/// matches with its type appended, e.g. `db` as `db.service`.
+pub const WILDCARD_CHARS: &[char] = &['*', '?', '[', ']'];
+
+/// The kind of unit, selected by `unit.type` or the file extension.
+///
+/// New unit types are added over time, so matches outside this crate need a wildcard arm.
+/// Matches inside the crate stay exhaustive so the compiler flags every place a new type
+/// has to be handled.
+#[derive(
+    Debug,
+    Deserialize,
+    Serialize,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumString,
+    EnumIter,
+    VariantArray,
+    IntoStaticStr,
+    Display,
+    Clone,
+    Copy,
+)]
 #[serde(rename_all = "lowercase")]
 #[strum(serialize_all = "lowercase")]
+#[non_exhaustive]
 pub enum UnitType {
+    #[default]
     Service,
     Target,
+    Timer,
+    Socket,
+}
+
+impl UnitType {
+    /// File extension of this type, without the dot. Same as its `Display` form.
+    pub fn extension(&self) -> &'static str {
+        self.into()
+    }
+
+    /// Every unit type, in declaration order.
+    pub fn all() -> &'static [UnitType] {
+        Self::VARIANTS
+    }
+
+    /// The type `load_unit` would infer from the file extension, without reading the file.
+    /// Fails with `InvalidExtension` or `UnsupportedUnitType` just like `load_unit`.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<UnitType, UnitLoadError> {
+        detect_type(path, &UnitTypeRegistry::default()).map(|(unit_type, _)| unit_type)
+    }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct UnitSection {
     #[serde(rename = "name")]
     pub unit_name: String,
@@ -19,40 +80,216 @@ pub struct UnitSection {
 
     #[serde(rename = "type")]
     pub unit_type: UnitType,
+
+    /// Aliases other units can use in their dependency lists to refer to this one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provides: Vec<String>,
+
+    /// Whether `GenerateOptions::default_after` applies to this unit.
+    #[serde(default = "enabled", skip_serializing_if = "is_enabled")]
+    pub default_dependencies: bool,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+impl Default for UnitSection {
+    fn default() -> Self {
+        Self {
+            unit_name: String::new(),
+            description: None,
+            unit_type: UnitType::default(),
+            provides: Vec::new(),
+            default_dependencies: true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
 pub struct TodoSection {
+    #[serde(default)]
     pub path: String,
+
+    #[serde(default)]
     pub args: Vec<String>,
+
+    #[serde(default, serialize_with = "sorted_map")]
     pub env: HashMap<String, String>,
+
+    /// `KEY=VALUE` files merged into `env` at load time, relative to the unit's directory.
+    /// Entries already in `env` take precedence. A leading `-` marks the file as optional.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_file: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    /// Commands run before `path`, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre: Vec<ExecCommand>,
+
+    /// Commands run after `path` has started, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post: Vec<ExecCommand>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct ExecCommand {
+    pub path: String,
+
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(
+    Debug, Deserialize, Serialize, PartialEq, Eq, EnumString, Display, Clone, Copy, Default,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum RestartPolicy {
+    #[default]
+    No,
+    OnFailure,
+    Always,
+    OnAbnormal,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
 pub struct ServiceSection {
-    #[serde(rename = "restart")]
+    #[serde(rename = "restart", default)]
+    pub restart_policy: RestartPolicy,
+
+    #[serde(rename = "restart_command")]
     pub command_on_restart: Option<String>,
 
     #[serde(rename = "stop")]
     pub command_on_stop: Option<String>,
+
+    #[serde(rename = "workdir")]
+    pub working_directory: Option<PathBuf>,
+
+    pub user: Option<String>,
+
+    pub group: Option<String>,
+
+    #[serde(default, with = "duration::option", skip_serializing_if = "Option::is_none")]
+    pub start_timeout: Option<Duration>,
+
+    #[serde(default, with = "duration::option", skip_serializing_if = "Option::is_none")]
+    pub stop_timeout: Option<Duration>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
 pub struct TargetSection {
+    /// The target is reached at most once, e.g. first-boot setup. `filter_once_targets` drops
+    /// it from regenerated plans, and `check_once_targets` rejects timers and restarting
+    /// services that depend on it, since they would need it again.
     #[serde(rename = "once")]
     pub is_runnable_once: bool,
 }
 
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct TimerSection {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_calendar: Option<String>,
+
+    #[serde(default, with = "duration::option", skip_serializing_if = "Option::is_none")]
+    pub on_boot_sec: Option<Duration>,
+
+    #[serde(default, with = "duration::option", skip_serializing_if = "Option::is_none")]
+    pub on_unit_active_sec: Option<Duration>,
+}
+
+impl TimerSection {
+    pub fn has_schedule(&self) -> bool {
+        self.on_calendar.is_some()
+            || self.on_boot_sec.is_some()
+            || self.on_unit_active_sec.is_some()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct SocketSection {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub listen_stream: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub listen_datagram: Vec<String>,
+
+    pub service: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
 pub struct DependencySection {
-    #[serde(rename = "before", default)]
+    /// Entries here and in `needs_after` may be patterns, see `WILDCARD_CHARS`.
+    #[serde(rename = "before", default, skip_serializing_if = "Vec::is_empty")]
     pub needs_before: Vec<String>,
 
-    #[serde(rename = "after", default)]
+    #[serde(rename = "after", default, skip_serializing_if = "Vec::is_empty")]
     pub needs_after: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wants: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<String>,
+}
+
+impl DependencySection {
+    pub fn all(&self) -> impl Iterator<Item = &String> {
+        self.needs_before
+            .iter()
+            .chain(&self.needs_after)
+            .chain(&self.requires)
+            .chain(&self.wants)
+            .chain(&self.conflicts)
+    }
+}
+
+/// Reverse dependencies: each target in `wanted_by` wants this unit and each one in
+/// `required_by` requires it. The unit is also ordered before those targets unless that would
+/// contradict an existing ordering.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct InstallSection {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wanted_by: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_by: Vec<String>,
+}
+
+impl InstallSection {
+    pub fn is_empty(&self) -> bool {
+        self.wanted_by.is_empty() && self.required_by.is_empty()
+    }
+}
+
+/// Checks that decide whether a unit is included at all, see `evaluate_conditions`.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct ConditionSection {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path_exists: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path_not_exists: Vec<String>,
+}
+
+impl ConditionSection {
+    pub fn is_empty(&self) -> bool {
+        self.path_exists.is_empty() && self.path_not_exists.is_empty()
+    }
+
+    /// Whether every condition holds on this machine. Relative paths are resolved against
+    /// the current directory.
+    pub fn holds(&self) -> bool {
+        self.path_exists.iter().all(|path| Path::new(path).exists())
+            && self.path_not_exists.iter().all(|path| !Path::new(path).exists())
+    }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// `UnitFile::default()` is an unnamed service skeleton. It does not pass `validate` until a
+/// name, a `todo.path` and a `[service]` section are filled in.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
 pub struct UnitFile {
     pub unit: UnitSection,
     pub todo: TodoSection,
@@ -63,38 +300,249 @@ pub struct UnitFile {
     #[serde(default)]
     pub target: Option<TargetSection>,
 
+    #[serde(default)]
+    pub timer: Option<TimerSection>,
+
+    #[serde(default)]
+    pub socket: Option<SocketSection>,
+
     #[serde(default)]
     pub dependency: DependencySection,
+
+    #[serde(default, skip_serializing_if = "ConditionSection::is_empty")]
+    pub condition: ConditionSection,
+
+    #[serde(default, skip_serializing_if = "InstallSection::is_empty")]
+    pub install: InstallSection,
+
+    #[serde(skip)]
+    pub(crate) origin: Option<PathBuf>,
 }
 
 impl UnitFile {
-    pub fn validate(&self) -> Result<(), Vec<String>> {
+    /// The file this unit was loaded from, or `None` if it was parsed from a string or built
+    /// in code.
+    pub fn origin(&self) -> Option<&Path> {
+        self.origin.as_deref()
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(self)
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
         let mut errors = Vec::new();
 
         if self.unit.unit_name.trim().is_empty() {
-            errors.push("Unit name cannot be empty".to_string());
+            errors.push(ValidationIssue::EmptyUnitName);
+        } else if !is_valid_unit_name(&self.unit.unit_name) {
+            errors.push(ValidationIssue::InvalidUnitName(self.unit.unit_name.clone()));
         }
 
         if self.todo.path.trim().is_empty() {
-            errors.push("Todo path cannot be empty".to_string());
+            errors.push(ValidationIssue::EmptyTodoPath);
+        }
+
+        for (i, command) in self.todo.pre.iter().enumerate() {
+            if command.path.trim().is_empty() {
+                errors.push(ValidationIssue::EmptyPreCommandPath(i));
+            }
+        }
+        for (i, command) in self.todo.post.iter().enumerate() {
+            if command.path.trim().is_empty() {
+                errors.push(ValidationIssue::EmptyPostCommandPath(i));
+            }
+        }
+
+        let mut keys: Vec<&String> = self.todo.env.keys().collect();
+        keys.sort();
+        for key in keys {
+            if !is_valid_env_key(key) {
+                errors.push(ValidationIssue::InvalidEnvKey(key.clone()));
+            }
         }
 
         match self.unit.unit_type {
             UnitType::Service if self.service.is_none() => {
-                errors.push("Service unit requires [service] section".to_string());
+                errors.push(ValidationIssue::MissingServiceSection);
             }
             UnitType::Target if self.target.is_none() => {
-                errors.push("Target unit requires [target] section".to_string());
+                errors.push(ValidationIssue::MissingTargetSection);
             }
+            UnitType::Timer => match &self.timer {
+                None => errors.push(ValidationIssue::MissingTimerSection),
+                Some(timer) if !timer.has_schedule() => {
+                    errors.push(ValidationIssue::EmptyTimerSchedule);
+                }
+                Some(_) => {}
+            },
+            UnitType::Socket => match &self.socket {
+                None => errors.push(ValidationIssue::MissingSocketSection),
+                Some(socket) => {
+                    if socket.listen_stream.is_empty() && socket.listen_datagram.is_empty() {
+                        errors.push(ValidationIssue::EmptySocketListen);
+                    }
+                    if socket.service.trim().is_empty() {
+                        errors.push(ValidationIssue::EmptySocketService);
+                    }
+                }
+            },
             _ => {}
         }
 
-        for dep in self.dependency.needs_before.iter().chain(&self.dependency.needs_after) {
+        if let Some(service) = &self.service {
+            for (field, timeout) in
+                [("start_timeout", service.start_timeout), ("stop_timeout", service.stop_timeout)]
+            {
+                if timeout == Some(Duration::ZERO) {
+                    errors.push(ValidationIssue::ZeroTimeout(field));
+                }
+            }
+        }
+
+        if let Some(dir) = self.service.as_ref().and_then(|s| s.working_directory.as_ref())
+            && !dir.is_absolute()
+        {
+            errors.push(ValidationIssue::RelativeWorkingDirectory(dir.clone()));
+        }
+
+        for alias in &self.unit.provides {
+            if !is_valid_unit_name(alias) {
+                errors.push(ValidationIssue::InvalidUnitName(alias.clone()));
+            }
+        }
+
+        let dependency = &self.dependency;
+        let is_valid = |dep: &String| {
+            let is_ordering =
+                dependency.needs_before.contains(dep) || dependency.needs_after.contains(dep);
+            self.is_valid_reference(dep) || (is_ordering && is_valid_pattern(dep))
+        };
+        for (i, dep) in dependency.all().enumerate() {
             if dep.trim().is_empty() {
-                errors.push("Dependency name cannot be empty".to_string());
+                errors.push(ValidationIssue::EmptyDependencyName(i));
+            } else if !is_valid(dep) {
+                errors.push(ValidationIssue::InvalidUnitName(dep.clone()));
+            }
+        }
+
+        for target in self.install.wanted_by.iter().chain(&self.install.required_by) {
+            if !self.is_valid_reference(target) {
+                errors.push(ValidationIssue::InvalidUnitName(target.clone()));
+            }
+        }
+
+        if let Some(socket) = &self.socket
+            && !socket.service.trim().is_empty()
+            && !self.is_valid_reference(&socket.service)
+        {
+            errors.push(ValidationIssue::InvalidUnitName(socket.service.clone()));
+        }
+
+        if dependency
+            .needs_before
+            .iter()
+            .chain(&dependency.needs_after)
+            .chain(&dependency.requires)
+            .chain(&dependency.wants)
+            .chain(self.socket.iter().map(|socket| &socket.service))
+            .chain(&self.install.wanted_by)
+            .chain(&self.install.required_by)
+            .any(|dep| *dep == self.unit.unit_name)
+        {
+            errors.push(ValidationIssue::SelfDependency);
+        }
+
+        for dep in &dependency.requires {
+            if dependency.conflicts.contains(dep) {
+                errors.push(ValidationIssue::RequiresAndConflicts(dep.clone()));
             }
         }
 
         if errors.is_empty() { Ok(()) } else { Err(errors) }
     }
+
+    /// Runs `validate` plus checks that are too strict to apply by default: the description
+    /// must be set and non-empty, and `todo.path` must be absolute unless the service sets a
+    /// working directory.
+    pub fn validate_strict(&self) -> Result<(), Vec<ValidationIssue>> {
+        let mut errors = self.validate().err().unwrap_or_default();
+
+        if self.unit.description.as_deref().is_none_or(|d| d.trim().is_empty()) {
+            errors.push(ValidationIssue::MissingDescription);
+        }
+
+        let path = Path::new(&self.todo.path);
+        let has_workdir =
+            self.service.as_ref().is_some_and(|service| service.working_directory.is_some());
+        if !self.todo.path.trim().is_empty() && !path.is_absolute() && !has_workdir {
+            errors.push(ValidationIssue::RelativeExecPath(self.todo.path.clone()));
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Templates may use `%i` in the units they reference.
+    fn is_valid_reference(&self, name: &str) -> bool {
+        if self.is_template() {
+            is_valid_unit_name(&name.replace(INSTANCE_SPECIFIER, "i"))
+        } else {
+            is_valid_unit_name(name)
+        }
+    }
+
+    /// Checks that `todo.path` exists and is an executable file. Unlike `validate`, this
+    /// depends on the machine it runs on, so it is never part of loading.
+    #[cfg(unix)]
+    pub fn validate_executable(&self) -> Result<(), ValidationIssue> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = &self.todo.path;
+        let metadata = std::fs::metadata(path)
+            .map_err(|_| ValidationIssue::ExecutableNotFound(path.clone()))?;
+
+        if !metadata.is_file() || metadata.permissions().mode() & 0o111 == 0 {
+            return Err(ValidationIssue::NotExecutable(path.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+fn is_valid_unit_name(name: &str) -> bool {
+    name.chars().all(|c| c.is_ascii_alphanumeric() || UNIT_NAME_PUNCTUATION.contains(&c))
+        && name.split('.').all(|segment| !segment.is_empty())
+}
+
+pub(crate) fn is_wildcard(name: &str) -> bool {
+    name.contains(WILDCARD_CHARS)
+}
+
+/// A wildcard entry that parses as a glob and would be a valid name with the wildcards
+/// filled in.
+fn is_valid_pattern(name: &str) -> bool {
+    is_wildcard(name)
+        && glob::Pattern::new(name).is_ok()
+        && is_valid_unit_name(&name.replace(WILDCARD_CHARS, "x"))
+}
+
+fn is_valid_env_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn enabled() -> bool {
+    true
+}
+
+fn is_enabled(value: &bool) -> bool {
+    *value
+}
+
+fn sorted_map<S: Serializer>(
+    map: &HashMap<String, String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
 }
@@ -0,0 +1,31 @@
+use crate::unit::errors::ValidationIssue;
+use crate::unit::types::UnitFile;
+
+/// An extra check run alongside `UnitFile::validate`. Issues from every validator are
+/// collected together rather than stopping at the first failure.
+pub trait Validator {
+    fn validate(&self, unit: &UnitFile) -> Result<(), Vec<ValidationIssue>>;
+}
+
+impl<F> Validator for F
+where
+    F: Fn(&UnitFile) -> Result<(), Vec<ValidationIssue>>,
+{
+    fn validate(&self, unit: &UnitFile) -> Result<(), Vec<ValidationIssue>> {
+        self(unit)
+    }
+}
+
+pub(crate) fn run_validators(
+    unit: &UnitFile,
+    validators: &[&dyn Validator],
+) -> Result<(), Vec<ValidationIssue>> {
+    let mut issues = unit.validate().err().unwrap_or_default();
+    for validator in validators {
+        if let Err(errors) = validator.validate(unit) {
+            issues.extend(errors);
+        }
+    }
+
+    if issues.is_empty() { Ok(()) } else { Err(issues) }
+}
@@ -0,0 +1,47 @@
+use std::io;
+use std::path::PathBuf;
+
+use neptune_units::{ErrorCategory, UnitLoadError, UnitType};
+
+#[test]
+fn every_variant_maps_to_its_category() {
+    let path = || PathBuf::from("a.service");
+    let io = || io::Error::from(io::ErrorKind::NotFound);
+    let toml = toml::from_str::<toml::Value>("=").unwrap_err();
+    let json = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+    let glob = glob::Pattern::new("[").unwrap_err();
+
+    let cases = [
+        (UnitLoadError::ReadError(path(), io()), ErrorCategory::Io),
+        (UnitLoadError::ReadDirError(path(), io()), ErrorCategory::Io),
+        (UnitLoadError::DirEntryError(path(), io()), ErrorCategory::Io),
+        (UnitLoadError::EnvFileError(path(), path(), io()), ErrorCategory::Io),
+        (UnitLoadError::ParseError(path(), toml), ErrorCategory::Parse),
+        (UnitLoadError::JsonParseError(path(), json), ErrorCategory::Parse),
+        (UnitLoadError::IniParseError(path(), "bad".into()), ErrorCategory::Parse),
+        (UnitLoadError::UndefinedVariable("a".into(), "X".into()), ErrorCategory::Parse),
+        (UnitLoadError::UnterminatedVariable("a".into()), ErrorCategory::Parse),
+        (UnitLoadError::InvalidGlobPattern("[".into(), glob), ErrorCategory::Parse),
+        (UnitLoadError::UnknownField(path(), "x".into()), ErrorCategory::Parse),
+        (UnitLoadError::InvalidEnvFileLine(path(), 1), ErrorCategory::Parse),
+        (UnitLoadError::IncludeCycle(vec![path()]), ErrorCategory::Parse),
+        (UnitLoadError::ValidationError(path(), vec![]), ErrorCategory::Validation),
+        (UnitLoadError::NoUnitsFound(path()), ErrorCategory::Validation),
+        (UnitLoadError::MissingDependency("a".into(), "b".into()), ErrorCategory::Validation),
+        (UnitLoadError::DuplicateUnitName("a".into(), path(), path()), ErrorCategory::Validation),
+        (UnitLoadError::InvalidExtension, ErrorCategory::Type),
+        (UnitLoadError::UnsupportedUnitType("md".into()), ErrorCategory::Type),
+        (
+            UnitLoadError::TypeMismatch {
+                path: path(),
+                expected: UnitType::Service,
+                found: UnitType::Target,
+            },
+            ErrorCategory::Type,
+        ),
+    ];
+
+    for (err, category) in cases {
+        assert_eq!(err.category(), category, "{err:?}");
+    }
+}
@@ -0,0 +1,68 @@
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use neptune_units::{ServiceSection, UnitFile, UnitType};
+
+/// A valid service unit with no dependencies.
+pub fn service(name: &str) -> UnitFile {
+    let mut unit = UnitFile::default();
+    unit.unit.unit_name = name.to_string();
+    unit.todo.path = format!("/usr/bin/{name}");
+    unit.service = Some(ServiceSection::default());
+    unit
+}
+
+/// A valid target unit with no dependencies.
+pub fn target(name: &str) -> UnitFile {
+    let mut unit = UnitFile::default();
+    unit.unit.unit_name = name.to_string();
+    unit.unit.unit_type = UnitType::Target;
+    unit.todo.path = "/bin/true".to_string();
+    unit.service = None;
+    unit.target = Some(Default::default());
+    unit
+}
+
+/// `service(name)` as the contents of a unit file.
+pub fn service_toml(name: &str) -> String {
+    service(name).to_toml_string().unwrap()
+}
+
+pub fn names(units: &[UnitFile]) -> Vec<&str> {
+    units.iter().map(|unit| unit.unit.unit_name.as_str()).collect()
+}
+
+/// A fresh directory under the system temp dir, removed on drop.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("neptune-units-{}-{n}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Writes `contents` to `name` inside the directory, creating parent directories.
+    pub fn write(&self, name: &str, contents: &str) -> PathBuf {
+        let path = self.0.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
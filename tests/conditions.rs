@@ -0,0 +1,53 @@
+mod common;
+
+use common::{names, service, target};
+use neptune_units::{GraphBuildError, SocketSection, UnitType, evaluate_conditions, generate};
+
+const MISSING: &str = "/nonexistent/neptune-units-condition";
+
+#[test]
+fn dropped_units_leave_no_dangling_references() {
+    let mut gated = target("gated");
+    gated.condition.path_exists.push(MISSING.to_string());
+    let mut daemon = service("daemon");
+    daemon.condition.path_exists.push(MISSING.to_string());
+
+    let mut installed = service("installed");
+    installed.install.wanted_by.push("gated".to_string());
+    installed.install.required_by.push("gated".to_string());
+    installed.dependency.needs_after.push("daemon".to_string());
+
+    let mut socket = service("daemon-socket");
+    socket.unit.unit_type = UnitType::Socket;
+    socket.service = None;
+    socket.socket = Some(SocketSection {
+        listen_stream: vec!["/run/daemon.sock".to_string()],
+        listen_datagram: Vec::new(),
+        service: "daemon".to_string(),
+    });
+
+    let mut units = vec![gated, daemon, installed, socket];
+    let dropped = evaluate_conditions(&mut units).unwrap();
+
+    assert_eq!(dropped, ["gated", "daemon", "daemon-socket"]);
+    assert_eq!(names(&units), ["installed"]);
+    assert!(units[0].install.is_empty());
+    assert!(units[0].dependency.needs_after.is_empty());
+    assert_eq!(names(&generate(&units).unwrap()), ["installed"]);
+}
+
+#[test]
+fn requiring_a_dropped_unit_fails_without_changes() {
+    let mut gated = service("gated");
+    gated.condition.path_exists.push(MISSING.to_string());
+    let mut app = service("app");
+    app.dependency.requires.push("gated".to_string());
+
+    let mut units = vec![gated, app];
+    let err = evaluate_conditions(&mut units).unwrap_err();
+
+    assert!(
+        matches!(err, GraphBuildError::ConditionFailed(unit, dep) if unit == "app" && dep == "gated")
+    );
+    assert_eq!(names(&units), ["gated", "app"]);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::service;
+use neptune_units::{
+    GenerateOptions, GraphBuildError, UnitFile, ValidationIssue, check_conflicts, generate,
+    transitive_dependencies_with,
+};
+
+fn conflicting() -> [UnitFile; 2] {
+    let mut a = service("a");
+    a.dependency.conflicts.push("b".into());
+    [a, service("b")]
+}
+
+#[test]
+fn conflicting_units_cannot_both_be_present() {
+    let units = conflicting();
+    for err in [check_conflicts(&units).unwrap_err(), generate(&units).unwrap_err()] {
+        assert!(
+            matches!(&err, GraphBuildError::ConflictViolation(a, b) if a == "a" && b == "b"),
+            "{err:?}"
+        );
+    }
+    assert!(check_conflicts(&units[..1]).is_ok());
+}
+
+#[test]
+fn conflicts_are_checked_before_sorting() {
+    let [mut a, mut b] = conflicting();
+    let mut c = service("c");
+    a.dependency.needs_after.push("c".into());
+    c.dependency.needs_after.push("b".into());
+    b.dependency.needs_after.push("a".into());
+
+    let err = generate(&[a, b, c]).unwrap_err();
+    assert!(matches!(err, GraphBuildError::ConflictViolation(..)), "{err:?}");
+}
+
+#[test]
+fn requiring_a_conflicting_unit_is_rejected() {
+    let mut a = service("a");
+    a.dependency.requires.push("b".into());
+    a.dependency.conflicts.push("b".into());
+    assert_eq!(a.validate(), Err(vec![ValidationIssue::RequiresAndConflicts("b".into())]));
+}
+
+#[test]
+fn requiring_a_conflicting_unit_through_an_alias_is_rejected() {
+    let mut a = service("a");
+    a.dependency.requires.push("database".into());
+    a.dependency.conflicts.push("b".into());
+    let mut b = service("b");
+    b.unit.provides.push("database".into());
+    assert_eq!(a.validate(), Ok(()));
+
+    let err = generate(&[a, b]).unwrap_err();
+    assert!(
+        matches!(&err, GraphBuildError::RequiresAndConflicts(a, b) if a == "a" && b == "b"),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn closure_conflicts_follow_case_insensitive_names() {
+    let mut a = service("a");
+    a.dependency.conflicts.push("B".into());
+    let mut app = service("app");
+    app.dependency.requires.extend(["a".into(), "b".into()]);
+    let units = [a, service("b"), app];
+
+    assert!(transitive_dependencies_with(&units, "app", &GenerateOptions::default()).is_ok());
+
+    let opts = GenerateOptions { case_insensitive_names: true, ..Default::default() };
+    let err = transitive_dependencies_with(&units, "app", &opts).unwrap_err();
+    assert!(
+        matches!(&err, GraphBuildError::ConflictViolation(a, b) if a == "a" && b == "B"),
+        "{err:?}"
+    );
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::service;
+use neptune_units::{
+    GenerateOptions, GraphBuildError, UnitFile, critical_path, critical_path_with, explain_order,
+    explain_order_with, generate, generate_levels_with, generate_with, transitive_dependencies,
+    transitive_dependencies_with,
+};
+
+const LEN: usize = 100_000;
+
+/// `u000000 -> u000001 -> ...`, each unit ordered after the previous one.
+fn chain() -> Vec<UnitFile> {
+    let name = |i: usize| format!("u{i:06}");
+    (0..LEN)
+        .map(|i| {
+            let mut unit = service(&name(i));
+            if i > 0 {
+                unit.dependency.needs_after.push(name(i - 1));
+            }
+            unit
+        })
+        .collect()
+}
+
+fn limited(limit: usize) -> GenerateOptions {
+    GenerateOptions { max_depth: Some(limit), ..GenerateOptions::default() }
+}
+
+#[test]
+fn long_chain_completes_without_overflow() {
+    let units = chain();
+    let first = units[0].unit.unit_name.clone();
+    let last = units[LEN - 1].unit.unit_name.clone();
+
+    assert_eq!(generate(&units).unwrap().len(), LEN);
+    assert_eq!(critical_path(&units).unwrap().len(), LEN);
+    assert_eq!(transitive_dependencies(&units, &last).unwrap().len(), LEN);
+    assert_eq!(explain_order(&units, &first, &last).unwrap().unwrap().len(), LEN);
+}
+
+#[test]
+fn every_traversal_honors_max_depth() {
+    let units = chain();
+    let first = units[0].unit.unit_name.clone();
+    let last = units[LEN - 1].unit.unit_name.clone();
+    let opts = limited(1000);
+    let exceeded = |err: GraphBuildError| {
+        assert!(
+            matches!(err, GraphBuildError::DepthLimitExceeded(ref name, 1000) if name == "u001000"),
+            "{err:?}"
+        );
+    };
+
+    exceeded(generate_with(&units, &opts).unwrap_err());
+    exceeded(critical_path_with(&units, &opts).unwrap_err());
+    exceeded(generate_levels_with(&units, &opts).unwrap_err());
+    exceeded(transitive_dependencies_with(&units, &last, &opts).unwrap_err());
+    exceeded(explain_order_with(&units, &first, &last, &opts).unwrap_err());
+
+    assert_eq!(critical_path_with(&units, &limited(LEN)).unwrap().len(), LEN);
+}
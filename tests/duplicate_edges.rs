@@ -0,0 +1,28 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::service;
+use neptune_units::{CollectingObserver, GenerateOptions, LoadWarning, ObserverHandle, UnitGraph};
+
+#[test]
+fn repeated_entries_add_one_edge_and_warn() {
+    let mut app = service("app");
+    app.dependency.needs_after = vec!["network".into(), "network".into()];
+    let mut network = service("network");
+    network.dependency.needs_before.push("app".into());
+
+    let observer = Arc::new(CollectingObserver::default());
+    let opts =
+        GenerateOptions { observer: ObserverHandle::from(observer.clone()), ..Default::default() };
+    let graph = UnitGraph::from_units_with(&[app.clone(), network], &opts).unwrap();
+
+    assert_eq!(graph.dependencies_of("app"), ["network"]);
+    assert_eq!(
+        observer.warnings(),
+        [LoadWarning::DuplicateDependency { unit: "app".into(), dependency: "network".into() }]
+    );
+
+    app.normalize();
+    assert_eq!(app.dependency.needs_after, ["network"]);
+}
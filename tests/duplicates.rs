@@ -0,0 +1,23 @@
+mod common;
+
+use common::{TempDir, service_toml};
+use neptune_units::{UnitLoadError, load_units_report};
+
+#[test]
+fn second_file_with_a_taken_name_is_skipped() {
+    let dir = TempDir::new();
+    let first = dir.write("a.service", &service_toml("network"));
+    let second = dir.write("b.service", &service_toml("network"));
+
+    let report = load_units_report(dir.path()).unwrap();
+    assert_eq!(report.units.len(), 1);
+    assert_eq!(report.units[0].origin(), Some(first.as_path()));
+    match report.skipped.as_slice() {
+        [(path, UnitLoadError::DuplicateUnitName(name, a, b))] => {
+            assert_eq!(name, "network");
+            assert_eq!((a, b), (&first, &second));
+            assert_eq!(path, &second);
+        }
+        skipped => panic!("{skipped:?}"),
+    }
+}
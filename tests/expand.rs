@@ -0,0 +1,43 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::service;
+use neptune_units::{UndefinedVars, UnitLoadError};
+
+fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn references_are_substituted_once() {
+    let mut unit = service("app");
+    unit.todo.path = "${HOME}/bin/app".into();
+    unit.todo.args = vec!["--config=$CONFIG_DIR/app.toml".into(), "$$HOME".into()];
+    unit.todo.env.insert("NESTED".into(), "${OUTER}".into());
+
+    let vars = vars(&[("HOME", "/home/app"), ("CONFIG_DIR", "/etc/app"), ("OUTER", "${HOME}")]);
+    unit.expand_env(&vars, UndefinedVars::Error).unwrap();
+
+    assert_eq!(unit.todo.path, "/home/app/bin/app");
+    assert_eq!(unit.todo.args, ["--config=/etc/app/app.toml", "$HOME"]);
+    // Values are inserted as they are, not expanded again.
+    assert_eq!(unit.todo.env["NESTED"], "${HOME}");
+}
+
+#[test]
+fn undefined_variables_follow_the_flag() {
+    let mut unit = service("app");
+    unit.todo.args = vec!["-v${MISSING}".into()];
+
+    let err = unit.clone().expand_env(&HashMap::new(), UndefinedVars::Error).unwrap_err();
+    match err {
+        UnitLoadError::UndefinedVariable(unit, var) => {
+            assert_eq!((unit, var), ("app".into(), "MISSING".into()))
+        }
+        err => panic!("{err:?}"),
+    }
+
+    unit.expand_env(&HashMap::new(), UndefinedVars::Empty).unwrap();
+    assert_eq!(unit.todo.args, ["-v"]);
+}
@@ -0,0 +1,26 @@
+mod common;
+
+use common::{service, target};
+use neptune_units::{GenerateOptions, to_mermaid, to_mermaid_with};
+
+fn edges(mermaid: &str) -> Vec<&str> {
+    mermaid.lines().map(str::trim).filter(|line| line.contains("-->")).collect()
+}
+
+#[test]
+fn mermaid_edges_follow_nodes_not_names() {
+    let mut ordered = service("app");
+    ordered.dependency.needs_after.push("db".to_string());
+    let units = [service("app"), ordered, service("db")];
+
+    assert_eq!(edges(&to_mermaid(&units).unwrap()), ["n1 --> n2"]);
+}
+
+#[test]
+fn mermaid_with_options_shows_default_edges() {
+    let units = [target("base"), service("app")];
+    let opts = GenerateOptions { default_after: Some("base".to_string()), ..Default::default() };
+
+    assert!(edges(&to_mermaid(&units).unwrap()).is_empty());
+    assert_eq!(edges(&to_mermaid_with(&units, &opts).unwrap()), ["n1 --> n0"]);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{TempDir, names, service_toml, target};
+use neptune_units::{
+    LoadOptions, UnitCache, UnitType, load_units_filtered, load_units_glob_with_options,
+    load_units_iter_with_options, load_units_recursive_with_options,
+    load_units_strict_with_options, load_units_with_options,
+};
+
+#[test]
+fn only_requested_types_are_returned() {
+    let dir = TempDir::new();
+    dir.write("app.service", &service_toml("app"));
+    dir.write("db.service", &service_toml("db"));
+    dir.write("network.target", &target("network").to_toml_string().unwrap());
+
+    let units = load_units_filtered(dir.path(), &[UnitType::Service]).unwrap();
+    assert_eq!(names(&units), ["app", "db"]);
+}
+
+#[test]
+fn other_types_are_not_parsed() {
+    let dir = TempDir::new();
+    dir.write("app.service", &service_toml("app"));
+    dir.write("broken.target", "[unit");
+
+    let options = LoadOptions { unit_types: Some(vec![UnitType::Service]), ..Default::default() };
+    let report = load_units_with_options(dir.path(), &options).unwrap();
+    assert_eq!(names(&report.units), ["app"]);
+    assert!(report.skipped.is_empty(), "{:?}", report.skipped);
+}
+
+#[test]
+fn cache_reload_applies_the_same_filter() {
+    let dir = TempDir::new();
+    dir.write("app.service", &service_toml("app"));
+    dir.write("network.target", &target("network").to_toml_string().unwrap());
+
+    let options = LoadOptions { unit_types: Some(vec![UnitType::Service]), ..Default::default() };
+    let loaded = load_units_with_options(dir.path(), &options).unwrap().units;
+    let mut cache = UnitCache::new();
+    let first = cache.reload_with_options(dir.path(), &options).unwrap();
+    let second = cache.reload_with_options(dir.path(), &options).unwrap();
+    assert_eq!(names(&first.units), names(&loaded));
+    assert_eq!(names(&second.units), names(&loaded));
+}
+
+#[test]
+fn iter_strict_recursive_and_glob_loads_apply_the_filter() {
+    let dir = TempDir::new();
+    dir.write("app.service", &service_toml("app"));
+    dir.write("broken.target", "[unit");
+    dir.write("sub/db.service", &service_toml("db"));
+
+    let options = LoadOptions { unit_types: Some(vec![UnitType::Service]), ..Default::default() };
+    // The iterator yields the `sub` directory as an `InvalidExtension` error, but nothing for
+    // the filtered out target.
+    let (iterated, errors): (Vec<_>, Vec<_>) =
+        load_units_iter_with_options(dir.path(), &options).partition(Result::is_ok);
+    let iterated: Vec<_> = iterated.into_iter().map(Result::unwrap).collect();
+    assert_eq!(names(&iterated), ["app"]);
+    assert!(
+        errors.iter().all(|err| matches!(err, Err(err) if err.0 == dir.path().join("sub"))),
+        "{errors:?}"
+    );
+    assert_eq!(names(&load_units_strict_with_options(dir.path(), &options).unwrap()), ["app"]);
+    let recursive = load_units_recursive_with_options(dir.path(), 1, &options).unwrap();
+    assert_eq!(names(&recursive), ["app", "db"]);
+    let pattern = format!("{}/**/*", dir.path().display());
+    assert_eq!(names(&load_units_glob_with_options(&pattern, &options).unwrap()), ["app", "db"]);
+}
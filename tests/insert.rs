@@ -0,0 +1,70 @@
+mod common;
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use common::service;
+use neptune_units::{
+    CollectingObserver, GenerateOptions, GraphBuildError, LoadWarning, MissingDepPolicy,
+    ObserverHandle, UnitFile, UnitGraph,
+};
+
+fn ordered(unit: &str, after: &[&str], before: &[&str]) -> UnitFile {
+    let mut unit = service(unit);
+    unit.dependency.needs_after = after.iter().map(|s| s.to_string()).collect();
+    unit.dependency.needs_before = before.iter().map(|s| s.to_string()).collect();
+    unit
+}
+
+#[test]
+fn inserted_units_join_the_topological_order() {
+    let mut graph = UnitGraph::from_units(&[ordered("b", &["a"], &[]), service("a")]).unwrap();
+    assert_eq!(graph.topo_order().unwrap(), [1, 0]);
+
+    graph.insert_unit(ordered("c", &["a"], &["b"])).unwrap();
+    assert_eq!(graph.topo_order().unwrap(), [1, 2, 0]);
+    assert_eq!(graph.dependencies_of("b"), ["a", "c"]);
+}
+
+#[test]
+fn failed_inserts_leave_the_graph_unchanged() {
+    let mut graph = UnitGraph::from_units(&[ordered("b", &["a"], &[]), service("a")]).unwrap();
+
+    let err = graph.insert_unit(ordered("c", &["b"], &["a"])).unwrap_err();
+    assert!(
+        matches!(&err, GraphBuildError::DependencyCycle(path) if path.contains(&"c".to_string()))
+    );
+    assert!(!graph.contains("c"));
+
+    let mut aliased = ordered("d", &["missing"], &[]);
+    aliased.unit.provides.push("d-alias".to_string());
+    assert!(matches!(graph.insert_unit(aliased), Err(GraphBuildError::LoadError(_))));
+    assert!(!graph.contains("d") && !graph.contains("d-alias"));
+
+    assert!(matches!(graph.insert_unit(service("a")), Err(GraphBuildError::UnitExists(_))));
+    assert_eq!(graph.topo_order().unwrap(), [1, 0]);
+    assert_eq!(graph.dependents_of("a"), ["b"]);
+}
+
+#[test]
+fn inserted_units_use_the_options_the_graph_was_built_with() {
+    let observer = Arc::new(CollectingObserver::default());
+    let opts = GenerateOptions {
+        missing_dependency: MissingDepPolicy::Warn,
+        mask: HashSet::from(["masked".to_string()]),
+        default_after: Some("base".to_string()),
+        observer: ObserverHandle::from(observer.clone()),
+        ..Default::default()
+    };
+    let mut graph =
+        UnitGraph::from_units_with(&[service("base"), service("masked")], &opts).unwrap();
+
+    graph.insert_unit(ordered("app", &["masked", "missing"], &[])).unwrap();
+
+    assert_eq!(graph.dependencies_of("app"), ["base"]);
+    assert_eq!(
+        observer.warnings(),
+        [LoadWarning::MissingDependency { unit: "app".into(), dependency: "missing".into() }]
+    );
+    assert_eq!(graph.topo_order().unwrap(), [0, 2, 1]);
+}
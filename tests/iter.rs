@@ -0,0 +1,33 @@
+mod common;
+
+use common::TempDir;
+use neptune_units::{UnitLoadError, load_units_iter, load_units_report};
+
+fn unit(name: &str) -> String {
+    format!(
+        "[unit]\nname = \"{name}\"\ntype = \"service\"\n\n\
+         [todo]\npath = \"/usr/bin/{name}\"\n\n[service]\n"
+    )
+}
+
+#[test]
+fn iter_matches_load_units() {
+    let dir = TempDir::new();
+    dir.write("a.service", &unit("a"));
+    dir.write("b.service", &unit("b"));
+    let duplicate = dir.write("c.service", &unit("a"));
+    dir.write("README", "not a unit");
+
+    let report = load_units_report(dir.path()).unwrap();
+    let (units, skipped): (Vec<_>, Vec<_>) = load_units_iter(dir.path()).partition(Result::is_ok);
+
+    let units: Vec<_> = units.into_iter().map(|unit| unit.unwrap().unit.unit_name).collect();
+    let expected: Vec<_> = report.units.iter().map(|unit| unit.unit.unit_name.clone()).collect();
+    assert_eq!(units, expected);
+    let skipped: Vec<_> = skipped.into_iter().map(|err| err.unwrap_err().0).collect();
+    let expected: Vec<_> = report.skipped.iter().map(|(path, _)| path.clone()).collect();
+    assert_eq!(skipped, expected);
+    assert!(report.skipped.iter().any(|(path, err)| {
+        *path == duplicate && matches!(err, UnitLoadError::DuplicateUnitName(..))
+    }));
+}
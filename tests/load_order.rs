@@ -0,0 +1,19 @@
+mod common;
+
+use common::{TempDir, names, service_toml};
+use neptune_units::{load_units, load_units_iter};
+
+#[test]
+fn directory_units_come_back_in_path_order() {
+    let dir = TempDir::new();
+    for name in ["zeta", "alpha", "mu", "beta"] {
+        dir.write(&format!("{name}.service"), &service_toml(name));
+    }
+
+    let expected = ["alpha", "beta", "mu", "zeta"];
+    for _ in 0..3 {
+        assert_eq!(names(&load_units(dir.path()).unwrap()), expected);
+    }
+    let streamed: Vec<_> = load_units_iter(dir.path()).map(Result::unwrap).collect();
+    assert_eq!(names(&streamed), expected);
+}
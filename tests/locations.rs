@@ -0,0 +1,54 @@
+mod common;
+
+use common::TempDir;
+use neptune_units::{Location, UnitLoadError, ValidationIssue, load_unit};
+
+fn issues(err: UnitLoadError) -> Vec<(ValidationIssue, Option<Location>)> {
+    match err {
+        UnitLoadError::ValidationError(_, issues) => {
+            issues.into_iter().map(|located| (located.issue, located.location)).collect()
+        }
+        other => panic!("expected a validation error, got {other:?}"),
+    }
+}
+
+#[test]
+fn issues_in_a_unit_with_includes_point_into_the_unit_file() {
+    let dir = TempDir::new();
+    dir.write("common.toml", "[unit]\ndescription = \"shared\"\n\n[service]\n");
+    let path = dir.write(
+        "app.service",
+        r#"include = ["common.toml"]
+
+[unit]
+name = "app"
+type = "service"
+
+# The todo section comes late so that its line differs from the merged text.
+
+
+[todo]
+path = ""
+"#,
+    );
+
+    let issues = issues(load_unit(&path).unwrap_err());
+    assert_eq!(
+        issues,
+        vec![(ValidationIssue::EmptyTodoPath, Some(Location { line: 11, column: 1 }))]
+    );
+}
+
+#[test]
+fn issues_about_included_keys_are_unlocated() {
+    let dir = TempDir::new();
+    dir.write("common.toml", "[service]\nworkdir = \"relative\"\n");
+    let path = dir.write(
+        "app.service",
+        "include = [\"common.toml\"]\n\n[unit]\nname = \"app\"\ntype = \"service\"\n\n[todo]\npath = \"/usr/bin/app\"\n",
+    );
+
+    let issues = issues(load_unit(&path).unwrap_err());
+    assert_eq!(issues.len(), 1);
+    assert!(matches!(issues[0], (ValidationIssue::RelativeWorkingDirectory(_), None)));
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::{names, service};
+use neptune_units::{
+    GenerateOptions, GraphBuildError, UnitGraph, generate_with, transitive_dependencies_with,
+};
+
+fn mask(names: &[&str]) -> HashSet<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn mask_entries_follow_case_insensitive_names() {
+    let mut b = service("b");
+    b.dependency.needs_after.push("A".to_string());
+    let units = [service("a"), b];
+
+    let opts =
+        GenerateOptions { mask: mask(&["A"]), case_insensitive_names: true, ..Default::default() };
+    assert_eq!(names(&generate_with(&units, &opts).unwrap()), ["b"]);
+
+    // With case significant, "A" names no loaded unit and masks nothing.
+    let opts = GenerateOptions { mask: mask(&["A"]), ..Default::default() };
+    assert_eq!(names(&generate_with(&units, &opts).unwrap()), ["a", "b"]);
+}
+
+#[test]
+fn references_through_an_alias_of_a_masked_unit_are_masked() {
+    let mut masked = service("masked");
+    masked.unit.provides.push("masked-alias".to_string());
+    let mut orders = service("orders");
+    orders.dependency.needs_after.push("masked-alias".to_string());
+    let mut requires = service("requires");
+    requires.dependency.requires.push("masked-alias".to_string());
+    let opts = GenerateOptions { mask: mask(&["masked"]), ..Default::default() };
+
+    let units = [masked.clone(), orders.clone()];
+    assert_eq!(names(&generate_with(&units, &opts).unwrap()), ["orders"]);
+
+    let units = [masked, orders, requires];
+    let err = generate_with(&units, &opts).unwrap_err();
+    assert!(
+        matches!(&err, GraphBuildError::MaskedDependency(unit, dep) if unit == "requires" && dep == "masked"),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn an_unmasked_provider_keeps_a_shared_alias() {
+    let mut masked = service("masked");
+    masked.unit.provides.push("shared".to_string());
+    let mut live = service("live");
+    live.unit.provides.push("shared".to_string());
+    let mut app = service("app");
+    app.dependency.requires.push("shared".to_string());
+    app.dependency.needs_after.push("shared".to_string());
+    let opts = GenerateOptions { mask: mask(&["masked"]), ..Default::default() };
+
+    let units = [masked, live, app];
+    assert_eq!(names(&generate_with(&units, &opts).unwrap()), ["live", "app"]);
+}
+
+#[test]
+fn wanting_a_masked_unit_drops_it_with_its_edges() {
+    let mut extra = service("extra");
+    extra.dependency.needs_after.push("base".to_string());
+    extra.dependency.needs_before.push("app".to_string());
+    let mut app = service("app");
+    app.dependency.wants.push("extra".to_string());
+    let units = [service("base"), extra, app];
+    let opts = GenerateOptions { mask: mask(&["extra"]), ..Default::default() };
+
+    let graph = UnitGraph::from_units_with(&units, &opts).unwrap();
+    assert!(graph.dependencies_of("extra").is_empty());
+    assert!(graph.dependents_of("extra").is_empty());
+    assert_eq!(names(&generate_with(&units, &opts).unwrap()), ["app", "base"]);
+}
+
+#[test]
+fn masked_units_are_left_out_of_a_closure() {
+    let mut app = service("app");
+    app.dependency.wants.push("extra".to_string());
+    let units = [service("extra"), app];
+    let opts = GenerateOptions { mask: mask(&["extra"]), ..Default::default() };
+
+    let closure = transitive_dependencies_with(&units, "app", &opts).unwrap();
+    assert_eq!(names(&closure), ["app"]);
+
+    // A masked root is left out like any other masked unit, as `generate_with` does.
+    let closure = transitive_dependencies_with(&units, "extra", &opts).unwrap();
+    assert!(closure.is_empty(), "{:?}", names(&closure));
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::service;
+use neptune_units::{
+    MergeStrategy, PartialDependencySection, PartialServiceSection, PartialTimerSection,
+    PartialTodoSection, PartialUnitFile, PartialUnitSection,
+};
+
+const FRAGMENT: &str = r#"
+[unit]
+provides = ["web"]
+default_dependencies = false
+
+[todo]
+env_file = ["extra.env"]
+command = "serve --port 80"
+
+[condition]
+path_exists = ["/etc/app.conf"]
+
+[install]
+wanted_by = ["multi-user.target"]
+required_by = ["web.target"]
+"#;
+
+#[test]
+fn fragments_cover_every_overridable_section() {
+    let partial: PartialUnitFile = toml::from_str(FRAGMENT).unwrap();
+    let mut unit = service("app");
+    unit.merge(&partial);
+
+    assert_eq!(unit.unit.provides, ["web"]);
+    assert!(!unit.unit.default_dependencies);
+    assert_eq!(unit.todo.env_file, ["extra.env"]);
+    assert_eq!(unit.todo.command.as_deref(), Some("serve --port 80"));
+    assert_eq!(unit.condition.path_exists, ["/etc/app.conf"]);
+    assert_eq!(unit.install.wanted_by, ["multi-user.target"]);
+    assert_eq!(unit.install.required_by, ["web.target"]);
+}
+
+#[test]
+fn merging_a_full_unit_copies_it() {
+    let partial: PartialUnitFile = toml::from_str(FRAGMENT).unwrap();
+    let mut source = service("app");
+    source.merge(&partial);
+
+    let mut copy = service("app");
+    copy.todo.path.clear();
+    copy.merge_with(&PartialUnitFile::from(&source), MergeStrategy::Replace);
+    assert_eq!(copy, source);
+}
+
+#[test]
+fn scalars_set_in_the_fragment_win() {
+    let mut unit = service("app");
+    unit.unit.description = Some("old".into());
+    unit.service.as_mut().unwrap().user = Some("root".into());
+    unit.todo.args = vec!["--old".into()];
+
+    unit.merge(&PartialUnitFile {
+        unit: Some(PartialUnitSection { description: Some("new".into()), ..Default::default() }),
+        service: Some(PartialServiceSection {
+            command_on_stop: Some("/usr/bin/stop".into()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+
+    assert_eq!(unit.unit.description.as_deref(), Some("new"));
+    let service = unit.service.as_ref().unwrap();
+    assert_eq!(service.command_on_stop.as_deref(), Some("/usr/bin/stop"));
+    // Unset fields keep their value.
+    assert_eq!(service.user.as_deref(), Some("root"));
+    assert_eq!(unit.todo.args, ["--old"]);
+}
+
+#[test]
+fn env_is_merged_key_by_key() {
+    let mut unit = service("app");
+    unit.todo.env.insert("KEEP".into(), "1".into());
+    unit.todo.env.insert("MODE".into(), "dev".into());
+
+    let mut todo = PartialTodoSection::default();
+    todo.env.insert("MODE".into(), "prod".into());
+    todo.env.insert("NEW".into(), "2".into());
+    unit.merge(&PartialUnitFile { todo: Some(todo), ..Default::default() });
+
+    assert_eq!(unit.todo.env.len(), 3);
+    assert_eq!(unit.todo.env["KEEP"], "1");
+    assert_eq!(unit.todo.env["MODE"], "prod");
+    assert_eq!(unit.todo.env["NEW"], "2");
+}
+
+#[test]
+fn dependency_lists_follow_the_strategy() {
+    let fragment = PartialUnitFile {
+        dependency: Some(PartialDependencySection {
+            needs_after: Some(vec!["b".into(), "c".into()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let mut base = service("app");
+    base.dependency.needs_after = vec!["a".into(), "b".into()];
+    base.dependency.requires = vec!["a".into()];
+
+    let mut union = base.clone();
+    union.merge_with(&fragment, MergeStrategy::Union);
+    assert_eq!(union.dependency.needs_after, ["a", "b", "c"]);
+
+    let mut replace = base;
+    replace.merge_with(&fragment, MergeStrategy::Replace);
+    assert_eq!(replace.dependency.needs_after, ["b", "c"]);
+    // Lists the fragment leaves unset are kept under either strategy.
+    assert_eq!(replace.dependency.requires, ["a"]);
+}
+
+#[test]
+fn missing_sections_are_created() {
+    let mut unit = service("app");
+    unit.merge(&PartialUnitFile {
+        timer: Some(PartialTimerSection {
+            on_calendar: Some("daily".into()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+    assert_eq!(unit.timer.unwrap().on_calendar.as_deref(), Some("daily"));
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use common::TempDir;
+use neptune_units::{LoadOptions, UnitLoadError, load_units, load_units_with_options};
+
+fn required() -> LoadOptions {
+    LoadOptions { require_units: true, ..Default::default() }
+}
+
+#[test]
+fn empty_directory_is_an_error_only_when_asked() {
+    let dir = TempDir::new();
+
+    assert!(load_units(dir.path()).unwrap().is_empty());
+    let err = load_units_with_options(dir.path(), &required()).unwrap_err();
+    assert!(matches!(&err, UnitLoadError::NoUnitsFound(path) if path == dir.path()), "{err:?}");
+}
+
+#[test]
+fn directory_of_invalid_files_is_an_error_only_when_asked() {
+    let dir = TempDir::new();
+    dir.write("broken.service", "[unit");
+    dir.write("README", "not a unit");
+
+    assert!(load_units(dir.path()).unwrap().is_empty());
+    let err = load_units_with_options(dir.path(), &required()).unwrap_err();
+    assert!(matches!(err, UnitLoadError::NoUnitsFound(_)), "{err:?}");
+}
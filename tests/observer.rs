@@ -0,0 +1,58 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::TempDir;
+use neptune_units::{
+    CollectingObserver, LoadOptions, LoadWarning, ObserverHandle, UnitCache,
+    load_enabled_units_with_options, load_units_with_overrides_and_options,
+};
+
+fn collecting() -> (Arc<CollectingObserver>, LoadOptions) {
+    let observer = Arc::new(CollectingObserver::default());
+    let options =
+        LoadOptions { observer: ObserverHandle::from(observer.clone()), ..Default::default() };
+    (observer, options)
+}
+
+#[test]
+fn enabled_units_report_to_the_given_observer() {
+    let base = TempDir::new();
+    let enabled = TempDir::new();
+    let link = enabled.write("plain.service", "not a link");
+
+    let (observer, options) = collecting();
+    load_enabled_units_with_options(base.path(), enabled.path(), &options).unwrap();
+    assert_eq!(
+        observer.warnings(),
+        vec![LoadWarning::IgnoredEnabledLink { path: link, reason: "not a symlink".into() }]
+    );
+}
+
+#[test]
+fn overrides_report_to_the_given_observer() {
+    let base = TempDir::new();
+    let overrides = TempDir::new();
+    let drop_in =
+        overrides.write("ghost.service.d/10-extra.conf", "").parent().unwrap().to_path_buf();
+
+    let (observer, options) = collecting();
+    load_units_with_overrides_and_options(base.path(), &[overrides.path()], &options).unwrap();
+    assert_eq!(
+        observer.warnings(),
+        vec![LoadWarning::IgnoredOverride { path: drop_in, unit: "ghost.service".into() }]
+    );
+}
+
+#[test]
+fn cache_reports_to_the_given_observer() {
+    let dir = TempDir::new();
+    let broken = dir.write("broken.service", "[unit");
+
+    let (observer, options) = collecting();
+    UnitCache::new().reload_with_options(dir.path(), &options).unwrap();
+    assert!(matches!(
+        observer.warnings().as_slice(),
+        [LoadWarning::SkippedFile { path, .. }] if *path == broken
+    ));
+}
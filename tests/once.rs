@@ -0,0 +1,64 @@
+mod common;
+
+use common::{names, service, target};
+use neptune_units::{
+    GraphBuildError, RestartPolicy, TimerSection, UnitFile, UnitLoadError, UnitType,
+    ValidationIssue, check_once_targets, filter_once_targets,
+};
+
+fn setup() -> UnitFile {
+    let mut unit = target("setup");
+    unit.target.as_mut().unwrap().is_runnable_once = true;
+    unit.unit.provides.push("first-boot".into());
+    unit
+}
+
+fn timer(name: &str) -> UnitFile {
+    let mut unit = service(name);
+    unit.unit.unit_type = UnitType::Timer;
+    unit.service = None;
+    unit.timer = Some(TimerSection { on_calendar: Some("daily".into()), ..Default::default() });
+    unit
+}
+
+fn after(mut unit: UnitFile, dep: &str) -> UnitFile {
+    unit.dependency.needs_after.push(dep.into());
+    unit
+}
+
+fn restarting(name: &str, policy: RestartPolicy) -> UnitFile {
+    let mut unit = service(name);
+    unit.service.as_mut().unwrap().restart_policy = policy;
+    unit
+}
+
+fn assert_rejected(units: &[UnitFile]) {
+    match check_once_targets(units).unwrap_err() {
+        GraphBuildError::LoadError(UnitLoadError::ValidationError(_, issues)) => {
+            let issues: Vec<_> = issues.into_iter().map(|located| located.issue).collect();
+            assert_eq!(issues, [ValidationIssue::OnceTargetDependency("setup".into())]);
+        }
+        err => panic!("{err:?}"),
+    }
+}
+
+#[test]
+fn timers_cannot_depend_on_once_targets() {
+    assert_rejected(&[setup(), after(timer("backup"), "setup")]);
+    assert_rejected(&[setup(), after(timer("backup"), "first-boot")]);
+}
+
+#[test]
+fn restarting_services_cannot_depend_on_once_targets() {
+    assert_rejected(&[setup(), after(restarting("web", RestartPolicy::Always), "setup")]);
+    assert!(
+        check_once_targets(&[setup(), after(restarting("web", RestartPolicy::No), "setup")])
+            .is_ok()
+    );
+}
+
+#[test]
+fn filtering_drops_only_once_targets() {
+    let plan = vec![setup(), target("network"), service("web"), timer("backup")];
+    assert_eq!(names(&filter_once_targets(plan)), ["network", "web", "backup"]);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{names, service, target};
+use neptune_units::{GraphBuildError, generate, generate_shutdown_order};
+
+#[test]
+fn unordered_units_are_sorted_by_name_regardless_of_input_order() {
+    let units = ["delta", "alpha", "echo", "charlie", "bravo"].map(service);
+    let expected = ["alpha", "bravo", "charlie", "delta", "echo"];
+
+    // Every rotation and its reverse, so no input order is special.
+    for shift in 0..units.len() {
+        let mut input = units.to_vec();
+        input.rotate_left(shift);
+        assert_eq!(names(&generate(&input).unwrap()), expected);
+        input.reverse();
+        assert_eq!(names(&generate(&input).unwrap()), expected);
+    }
+}
+
+#[test]
+fn ties_are_broken_by_name_at_every_step() {
+    // `a` waits for `c`, then sorts ahead of `d`, which was ready all along.
+    let mut a = service("a");
+    a.dependency.needs_after.push("c".into());
+    let units = [a, service("d"), service("c"), service("b")];
+
+    assert_eq!(names(&generate(&units).unwrap()), ["b", "c", "a", "d"]);
+}
+
+#[test]
+fn units_stop_before_what_they_started_after() {
+    let mut app = service("app");
+    app.dependency.needs_after.push("network".into());
+    let mut web = service("web");
+    web.dependency.needs_before.push("app".into());
+    let units = [app, target("network"), web];
+
+    assert_eq!(names(&generate(&units).unwrap()), ["network", "web", "app"]);
+    assert_eq!(names(&generate_shutdown_order(&units).unwrap()), ["app", "web", "network"]);
+}
+
+#[test]
+fn contradictory_pair_is_reported_as_such() {
+    let mut a = service("a");
+    a.dependency.needs_before.push("b".into());
+    a.dependency.needs_after.push("b".into());
+
+    match generate(&[a, service("b")]).unwrap_err() {
+        GraphBuildError::ContradictoryOrdering(x, y) => {
+            let mut pair = [x, y];
+            pair.sort();
+            assert_eq!(pair, ["a", "b"]);
+        }
+        err => panic!("{err:?}"),
+    }
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::service;
+use neptune_units::generate_plan_json;
+use serde_json::Value;
+
+fn depends_on(plan: &Value) -> Vec<(String, Vec<String>)> {
+    plan["units"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|unit| {
+            let deps = unit["depends_on"].as_array().unwrap();
+            let deps = deps.iter().map(|dep| dep.as_str().unwrap().to_string()).collect();
+            (unit["name"].as_str().unwrap().to_string(), deps)
+        })
+        .collect()
+}
+
+#[test]
+fn units_sharing_a_name_keep_their_own_dependencies() {
+    let mut ordered = service("app");
+    ordered.dependency.needs_after.push("db".to_string());
+    let units = [service("app"), ordered, service("db")];
+
+    let plan: Value = serde_json::from_str(&generate_plan_json(&units).unwrap()).unwrap();
+    let mut entries = depends_on(&plan);
+    entries.sort();
+    assert_eq!(
+        entries,
+        [
+            ("app".to_string(), vec![]),
+            ("app".to_string(), vec!["db".to_string()]),
+            ("db".to_string(), vec![]),
+        ]
+    );
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use std::path::Path;
+
+use common::{TempDir, service};
+use neptune_units::{ExecCommand, LoadOptions, load_unit_with_options};
+
+#[test]
+fn relative_paths_are_joined_onto_the_base() {
+    let mut unit = service("app");
+    unit.todo.path = "./scripts/foo".into();
+    unit.todo.pre = vec![
+        ExecCommand { path: "/usr/bin/setup".into(), args: vec![] },
+        ExecCommand { path: "sh".into(), args: vec![] },
+    ];
+    unit.todo.env_file = vec!["app.env".into(), "-./local.env".into()];
+
+    unit.resolve_paths(Path::new("/etc/neptune/web"));
+
+    assert_eq!(unit.todo.path, "/etc/neptune/web/scripts/foo");
+    assert_eq!(unit.todo.pre[0].path, "/usr/bin/setup");
+    assert_eq!(unit.todo.pre[1].path, "sh");
+    assert_eq!(unit.todo.env_file, ["/etc/neptune/web/app.env", "-/etc/neptune/web/local.env"]);
+}
+
+#[test]
+fn loading_resolves_against_the_unit_directory() {
+    let dir = TempDir::new();
+    let unit = service("app").to_toml_string().unwrap().replace("/usr/bin/app", "./scripts/foo");
+    let path = dir.write("nested/web/app.service", &unit);
+
+    let options = LoadOptions { resolve_paths: true, ..Default::default() };
+    let unit = load_unit_with_options(&path, &options).unwrap();
+    let expected = path.parent().unwrap().join("scripts/foo");
+    assert_eq!(Path::new(&unit.todo.path), expected);
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{service, target};
+use neptune_units::{ExecCommand, RestartPolicy, UnitType, parse_unit};
+
+#[test]
+fn serialized_units_parse_back_equal() {
+    let mut app = service("app");
+    app.unit.description = Some("The app".into());
+    app.unit.provides.push("web".into());
+    app.todo.args = vec!["--port".into(), "80".into()];
+    app.todo.env.insert("MODE".into(), "prod".into());
+    app.todo.pre.push(ExecCommand { path: "/usr/bin/migrate".into(), args: vec![] });
+    app.dependency.needs_after.push("network".into());
+    app.dependency.requires.push("network".into());
+    app.condition.path_exists.push("/etc/app.conf".into());
+    app.install.wanted_by.push("multi-user".into());
+    let service = app.service.as_mut().unwrap();
+    service.restart_policy = RestartPolicy::OnFailure;
+    service.user = Some("app".into());
+    service.start_timeout = Some(Duration::from_secs(90));
+
+    let mut network = target("network");
+    network.unit.default_dependencies = false;
+    network.dependency.conflicts.push("offline".into());
+
+    for (unit, unit_type) in [(app, UnitType::Service), (network, UnitType::Target)] {
+        let toml = unit.to_toml_string().unwrap();
+        assert_eq!(parse_unit(&toml, unit_type).unwrap(), unit, "{toml}");
+    }
+}
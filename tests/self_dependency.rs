@@ -0,0 +1,30 @@
+mod common;
+
+use common::{TempDir, service};
+use neptune_units::{UnitFile, UnitLoadError, ValidationIssue, load_unit};
+
+fn depends_on_itself(edit: impl Fn(&mut UnitFile)) -> UnitFile {
+    let mut unit = service("a");
+    edit(&mut unit);
+    unit
+}
+
+#[test]
+fn before_and_after_self_are_rejected() {
+    let before = depends_on_itself(|unit| unit.dependency.needs_before.push("a".into()));
+    let after = depends_on_itself(|unit| unit.dependency.needs_after.push("a".into()));
+
+    for unit in [before, after] {
+        assert_eq!(unit.validate(), Err(vec![ValidationIssue::SelfDependency]));
+
+        let dir = TempDir::new();
+        let path = dir.write("a.service", &unit.to_toml_string().unwrap());
+        match load_unit(&path).unwrap_err() {
+            UnitLoadError::ValidationError(_, issues) => {
+                let issues: Vec<_> = issues.into_iter().map(|located| located.issue).collect();
+                assert_eq!(issues, [ValidationIssue::SelfDependency]);
+            }
+            err => panic!("{err:?}"),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use std::path::Path;
+
+use common::TempDir;
+use neptune_units::{
+    LoadOptions, UnitFile, UnitLoadError, UnitTypeRegistry, detect_type, load_unit,
+    parse_unit_file, read_unit_file,
+};
+
+fn load_in_steps(path: &Path) -> Result<UnitFile, UnitLoadError> {
+    let kind = detect_type(path, &UnitTypeRegistry::default())?;
+    let contents = read_unit_file(path)?;
+    parse_unit_file(path, &contents, kind, &LoadOptions::default())
+}
+
+#[test]
+fn steps_compose_to_load_unit_for_every_format() {
+    let dir = TempDir::new();
+    dir.write("app.env", "FROM=file\n");
+    dir.write("common.toml", "[unit]\ndescription = \"shared\"\n");
+    let paths = [
+        dir.write(
+            "toml.service",
+            "include = [\"common.toml\"]\n\n[unit]\nname = \"toml\"\ntype = \"service\"\n\n\
+             [todo]\npath = \"/usr/bin/toml\"\nenv_file = [\"app.env\"]\n\n[service]\n",
+        ),
+        dir.write(
+            "json.service.json",
+            r#"{"unit": {"name": "json", "type": "service"},
+                "todo": {"path": "/usr/bin/json", "env_file": ["app.env"]},
+                "service": {}}"#,
+        ),
+        dir.write("ini.service", "[Unit]\nDescription=ini\n\n[Service]\nExecStart=/usr/bin/ini\n"),
+    ];
+
+    for path in &paths {
+        let loaded = load_unit(path).unwrap();
+        assert_eq!(load_in_steps(path).unwrap(), loaded);
+        assert_eq!(loaded.origin(), Some(path.as_path()));
+    }
+    let toml = load_unit(&paths[0]).unwrap();
+    assert_eq!(toml.unit.description.as_deref(), Some("shared"));
+    assert_eq!(toml.todo.env.get("FROM").map(String::as_str), Some("file"));
+}
+
+#[test]
+fn each_step_reports_its_own_errors() {
+    let dir = TempDir::new();
+    let registry = UnitTypeRegistry::default();
+
+    assert!(matches!(
+        detect_type(dir.path().join("notes.txt"), &registry),
+        Err(UnitLoadError::UnsupportedUnitType(_))
+    ));
+    let missing = dir.path().join("missing.service");
+    assert!(detect_type(&missing, &registry).is_ok());
+    assert!(matches!(read_unit_file(&missing), Err(UnitLoadError::ReadError(..))));
+}
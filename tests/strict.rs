@@ -0,0 +1,41 @@
+mod common;
+
+use common::{TempDir, names};
+use neptune_units::{UnitLoadError, load_units, load_units_strict};
+
+const UNIT: &str = r#"
+[unit]
+name = "a"
+type = "service"
+
+[todo]
+path = "/usr/bin/a"
+
+[service]
+"#;
+
+#[test]
+fn non_unit_files_are_ignored() {
+    let dir = TempDir::new();
+    dir.write("a.service", UNIT);
+    dir.write("README", "not a unit");
+    dir.write("notes.md", "# not a unit either");
+
+    let units = load_units_strict(dir.path()).unwrap();
+    assert_eq!(units.len(), 1);
+    assert_eq!(units[0].unit.unit_name, "a");
+}
+
+#[test]
+fn broken_unit_file_stops_the_load() {
+    let dir = TempDir::new();
+    dir.write("a.service", UNIT);
+    let broken = dir.write("b.service", "[unit");
+
+    let (path, err) = *load_units_strict(dir.path()).unwrap_err();
+    assert_eq!(path, broken);
+    assert!(matches!(err, UnitLoadError::ParseError(..)), "{err:?}");
+
+    // The permissive loader skips the broken file and keeps the valid unit.
+    assert_eq!(names(&load_units(dir.path()).unwrap()), ["a"]);
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "tar")]
+
+use std::sync::Arc;
+
+use neptune_units::{
+    CollectingObserver, LoadOptions, LoadWarning, ObserverHandle, UnitLoadError, UnitType,
+    load_units_from_tar, load_units_from_tar_with_options,
+};
+
+fn archive(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, contents.as_bytes()).unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+const APP: &str = r#"
+include = ["../shared/common.toml"]
+
+[unit]
+name = "app"
+type = "service"
+
+[todo]
+path = "/usr/bin/app"
+env_file = ["app.env"]
+
+[service]
+"#;
+
+#[test]
+fn includes_and_env_files_are_read_from_the_archive() {
+    let data = archive(&[
+        ("units/app.service", APP),
+        ("units/app.env", "FROM=archive\n"),
+        ("shared/common.toml", "[unit]\ndescription = \"shared\"\n"),
+    ]);
+
+    let units = load_units_from_tar(data.as_slice()).unwrap();
+    assert_eq!(units.len(), 1);
+    let app = &units[0];
+    assert_eq!(app.unit.description.as_deref(), Some("shared"));
+    assert_eq!(app.todo.env.get("FROM").map(String::as_str), Some("archive"));
+}
+
+#[test]
+fn references_missing_from_the_archive_are_not_read_from_disk() {
+    // Tests run from the crate root, so host lookups would find both of these files.
+    let include_host =
+        APP.replace("../shared/common.toml", "Cargo.toml").replace("env_file = [\"app.env\"]", "");
+    let env_from_host =
+        APP.replace("include = [\"../shared/common.toml\"]", "").replace("app.env", "rustfmt.toml");
+
+    for unit in [include_host, env_from_host] {
+        let data = archive(&[("app.service", &unit)]);
+        assert!(load_units_from_tar(data.as_slice()).unwrap().is_empty());
+    }
+}
+
+#[test]
+fn skipped_entries_go_to_the_given_observer() {
+    let data = archive(&[("units/broken.service", "[unit")]);
+    let observer = Arc::new(CollectingObserver::default());
+    let options =
+        LoadOptions { observer: ObserverHandle::from(observer.clone()), ..Default::default() };
+
+    assert!(load_units_from_tar_with_options(data.as_slice(), &options).unwrap().is_empty());
+    assert!(matches!(
+        observer.warnings().as_slice(),
+        [LoadWarning::SkippedFile { path, .. }] if path.ends_with("units/broken.service")
+    ));
+}
+
+#[test]
+fn unreadable_archive_entry_keeps_earlier_units() {
+    let unit =
+        APP.replace("include = [\"../shared/common.toml\"]", "").replace("app.env", "-app.env");
+    let mut data = archive(&[("app.service", &unit), ("other.service", &unit)]);
+    // Corrupt the second header so its checksum no longer matches.
+    let second = 512 + unit.len().div_ceil(512) * 512;
+    data[second] ^= 0xff;
+
+    let observer = Arc::new(CollectingObserver::default());
+    let options =
+        LoadOptions { observer: ObserverHandle::from(observer.clone()), ..Default::default() };
+    let units = load_units_from_tar_with_options(data.as_slice(), &options).unwrap();
+
+    assert_eq!(units.len(), 1);
+    assert_eq!(units[0].unit.unit_name, "app");
+    assert!(matches!(
+        observer.warnings().as_slice(),
+        [LoadWarning::SkippedFile { path, .. }] if path.as_os_str() == "<archive>"
+    ));
+}
+
+const NETWORK: &str = "[unit]\nname = \"network\"\ntype = \"target\"\n\n\
+                       [todo]\npath = \"/bin/true\"\n\n[target]\nonce = false\n";
+
+#[test]
+fn archives_are_filtered_by_type() {
+    let unit =
+        APP.replace("include = [\"../shared/common.toml\"]", "").replace("app.env", "-app.env");
+    let data =
+        archive(&[("app.service", &unit), ("network.target", NETWORK), ("broken.target", "[unit")]);
+
+    let options = LoadOptions { unit_types: Some(vec![UnitType::Target]), ..Default::default() };
+    let units = load_units_from_tar_with_options(data.as_slice(), &options).unwrap();
+    assert_eq!(units.len(), 1);
+    assert_eq!(units[0].unit.unit_name, "network");
+
+    let options = LoadOptions { unit_types: Some(vec![UnitType::Timer]), ..Default::default() };
+    assert!(load_units_from_tar_with_options(data.as_slice(), &options).unwrap().is_empty());
+}
+
+#[test]
+fn archives_honor_require_units() {
+    let data = archive(&[("broken.service", "[unit")]);
+    let options = LoadOptions { require_units: true, ..Default::default() };
+
+    let err = load_units_from_tar_with_options(data.as_slice(), &options).unwrap_err();
+    assert!(matches!(err, UnitLoadError::NoUnitsFound(_)), "{err:?}");
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "tokio")]
+
+mod common;
+
+use common::{TempDir, names, service_toml, target};
+use neptune_units::{
+    LoadOptions, UnitLoadError, UnitType, load_units_async_with_options, load_units_with_options,
+};
+
+#[tokio::test(flavor = "current_thread")]
+async fn async_loading_filters_by_type_like_sync_loading() {
+    let dir = TempDir::new();
+    dir.write("app.service", &service_toml("app"));
+    dir.write("network.target", &target("network").to_toml_string().unwrap());
+    dir.write("broken.target", "[unit");
+
+    let options = LoadOptions { unit_types: Some(vec![UnitType::Service]), ..Default::default() };
+    let units = load_units_async_with_options(dir.path(), &options).await.unwrap();
+    let sync = load_units_with_options(dir.path(), &options).unwrap().units;
+    assert_eq!(names(&units), ["app"]);
+    assert_eq!(units, sync);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn async_loading_honors_require_units() {
+    let dir = TempDir::new();
+    dir.write("broken.service", "[unit");
+
+    let options = LoadOptions { require_units: true, ..Default::default() };
+    let err = load_units_async_with_options(dir.path(), &options).await.unwrap_err();
+    assert!(matches!(&err, UnitLoadError::NoUnitsFound(path) if path == dir.path()), "{err:?}");
+}
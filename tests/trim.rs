@@ -0,0 +1,41 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{TempDir, names, target};
+use neptune_units::{
+    CollectingObserver, LoadOptions, LoadWarning, ObserverHandle, generate, load_units_with_options,
+};
+
+const APP: &str = r#"
+[unit]
+name = "app "
+type = "service"
+
+[todo]
+path = "/usr/bin/app"
+
+[service]
+
+[dependency]
+after = [" network "]
+"#;
+
+#[test]
+fn padded_names_resolve_after_trimming() {
+    let dir = TempDir::new();
+    let app = dir.write("app.service", APP);
+    dir.write("network.target", &target("network").to_toml_string().unwrap());
+
+    let observer = Arc::new(CollectingObserver::default());
+    let options =
+        LoadOptions { observer: ObserverHandle::from(observer.clone()), ..Default::default() };
+    let units = load_units_with_options(dir.path(), &options).unwrap().units;
+
+    assert_eq!(names(&generate(&units).unwrap()), ["network", "app"]);
+    assert_eq!(
+        observer.warnings(),
+        ["app ", " network "]
+            .map(|value| LoadWarning::TrimmedName { path: app.clone(), value: value.into() })
+    );
+}
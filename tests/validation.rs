@@ -0,0 +1,73 @@
+mod common;
+
+use common::service;
+use neptune_units::{UNIT_NAME_PUNCTUATION, UnitFile, ValidationIssue};
+
+fn issues(unit: &UnitFile) -> Vec<ValidationIssue> {
+    unit.validate().err().unwrap_or_default()
+}
+
+#[test]
+fn unit_names_are_limited_to_the_allowlist() {
+    for name in ["my unit", "sys/net", "tab\tname", "bell\u{7}", "a..b", ".hidden", "end."] {
+        assert_eq!(issues(&service(name)), [ValidationIssue::InvalidUnitName(name.into())]);
+    }
+
+    let allowed: String = UNIT_NAME_PUNCTUATION.iter().collect();
+    assert_eq!(issues(&service(&format!("Net0{allowed}x"))), []);
+}
+
+#[test]
+fn dependency_entries_use_the_same_rule() {
+    let mut unit = service("app");
+    unit.dependency.requires.push("net/work".into());
+    assert_eq!(issues(&unit), [ValidationIssue::InvalidUnitName("net/work".into())]);
+}
+
+#[test]
+fn env_keys_must_be_identifiers() {
+    for key in ["FOO BAR", "1X", "A=B", ""] {
+        let mut unit = service("app");
+        unit.todo.env.insert(key.into(), "value".into());
+        assert_eq!(issues(&unit), [ValidationIssue::InvalidEnvKey(key.into())]);
+    }
+
+    let mut unit = service("app");
+    unit.todo.env.insert("_VALID_KEY_2".into(), "value".into());
+    assert_eq!(issues(&unit), []);
+}
+
+fn documented(name: &str) -> UnitFile {
+    let mut unit = service(name);
+    unit.unit.description = Some(format!("The {name} service"));
+    unit
+}
+
+fn strict_issues(unit: &UnitFile) -> Vec<ValidationIssue> {
+    unit.validate_strict().err().unwrap_or_default()
+}
+
+#[test]
+fn strict_mode_requires_absolute_exec_paths() {
+    assert_eq!(strict_issues(&documented("app")), []);
+
+    let mut relative = documented("app");
+    relative.todo.path = "bin/app".into();
+    assert_eq!(issues(&relative), []);
+    assert_eq!(strict_issues(&relative), [ValidationIssue::RelativeExecPath("bin/app".into())]);
+
+    relative.service.as_mut().unwrap().working_directory = Some("/srv/app".into());
+    assert_eq!(strict_issues(&relative), []);
+}
+
+#[test]
+fn strict_mode_requires_a_description() {
+    let mut unit = documented("app");
+    assert_eq!(strict_issues(&unit), []);
+
+    for description in [Some(String::new()), Some("  ".into()), None] {
+        unit.unit.description = description;
+        assert_eq!(issues(&unit), []);
+        assert_eq!(strict_issues(&unit), [ValidationIssue::MissingDescription]);
+    }
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{names, service, target};
+use neptune_units::{
+    CollectingObserver, GenerateOptions, LoadWarning, ObserverHandle, UnitFile, generate,
+    generate_with,
+};
+
+fn after(mut unit: UnitFile, patterns: &[&str]) -> UnitFile {
+    unit.dependency.needs_after.extend(patterns.iter().map(|p| p.to_string()));
+    unit
+}
+
+#[test]
+fn suffix_pattern_orders_after_every_match() {
+    let units = [
+        after(target("all"), &["*.service"]),
+        service("b.service"),
+        // Matches its own pattern, which must not become a self-edge.
+        after(service("a.service"), &["*.service"]),
+        // Services match with their type appended, targets do not.
+        service("db"),
+        target("other"),
+    ];
+
+    let order = generate(&units).unwrap();
+    assert_eq!(names(&order), ["b.service", "db", "a.service", "all", "other"]);
+}
+
+#[test]
+fn prefix_pattern_orders_after_every_match() {
+    let mut web = service("web");
+    web.dependency.needs_before.push("net-*".into());
+    let units = [service("net-a"), web, service("net-b"), after(service("app"), &["net-*"])];
+
+    assert_eq!(names(&generate(&units).unwrap()), ["web", "net-a", "net-b", "app"]);
+}
+
+#[test]
+fn unmatched_pattern_only_warns() {
+    let observer = Arc::new(CollectingObserver::default());
+    let opts =
+        GenerateOptions { observer: ObserverHandle::from(observer.clone()), ..Default::default() };
+
+    let order = generate_with(&[after(service("app"), &["gw-*"])], &opts).unwrap();
+    assert_eq!(names(&order), ["app"]);
+    assert_eq!(
+        observer.warnings(),
+        [LoadWarning::UnmatchedPattern { unit: "app".into(), pattern: "gw-*".into() }]
+    );
+}